@@ -0,0 +1,197 @@
+use crate::board::Board;
+use crate::candidates::{digit_bit, digits};
+use crate::engine::SearchEngine;
+use crate::error::SudokuError;
+
+/// Which empty cells hold the same digit in every solution of a board and
+/// which could hold more than one, worked out by enumerating the solutions
+/// with the backtracking search.
+///
+/// Enumeration stops after `limit` solutions. When it stops early the digits
+/// are only those seen so far, so a cell may look fixed that another solution
+/// would show to be ambiguous. `is_complete` tells the two cases apart.
+///
+/// ```
+/// use sudoku_solver::{Backbone, Board};
+///
+/// let backbone = Backbone::new(&Board::new(), 10).unwrap();
+///
+/// assert!(!backbone.is_complete());
+/// assert_eq!(Some(1), backbone.fixed(0, 0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backbone {
+    board: Board,
+    // every digit a cell held in one of the solutions seen
+    masks: Vec<u32>,
+    solutions: usize,
+    complete: bool,
+}
+
+impl Backbone {
+    /// Looks at up to `limit` solutions of the board, and always at least one.
+    /// Invalid boards fail with their first conflict and boards without a
+    /// solution with `Unsolvable`.
+    pub fn new(board: &Board, limit: usize) -> Result<Backbone, SudokuError> {
+        let limit = limit.max(1);
+        let mut engine = SearchEngine::new(board)?;
+        let size = board.size();
+        let mut backbone = Backbone {
+            board: board.clone(),
+            masks: vec![0; size * size],
+            solutions: 0,
+            complete: false,
+        };
+
+        loop {
+            let solution = match engine.next_solution() {
+                Some(solution) => solution,
+                None => {
+                    backbone.complete = true;
+                    break;
+                }
+            };
+
+            // one more solution past the limit only shows there are others
+            if backbone.solutions == limit {
+                break;
+            }
+
+            for ((row, col), value) in solution.cells() {
+                backbone.masks[row * size + col] |= digit_bit(value);
+            }
+            backbone.solutions += 1;
+        }
+
+        if backbone.solutions == 0 && backbone.complete {
+            return Err(SudokuError::Unsolvable);
+        }
+
+        Ok(backbone)
+    }
+
+    /// How many solutions were enumerated.
+    pub fn solutions(&self) -> usize {
+        self.solutions
+    }
+
+    /// Whether every solution was seen, which is also the case when there
+    /// are exactly `limit` of them.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// The digits a cell held across the solutions as a mask, just its own
+    /// digit for a given.
+    pub fn digits(&self, row: usize, col: usize) -> u32 {
+        self.masks[row * self.board.size() + col]
+    }
+
+    /// The digit a cell holds in every solution, if there is only one.
+    pub fn fixed(&self, row: usize, col: usize) -> Option<u8> {
+        let mask = self.digits(row, col);
+
+        if mask.count_ones() == 1 {
+            digits(mask).next()
+        } else {
+            None
+        }
+    }
+
+    /// Every empty cell that holds the same digit in every solution, in
+    /// row-major order.
+    pub fn fixed_cells(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        self.empty_cells()
+            .filter_map(move |(row, col)| self.fixed(row, col).map(|digit| ((row, col), digit)))
+    }
+
+    /// Every empty cell with more than one possible digit and the mask of
+    /// those digits, in row-major order. This is where a draft puzzle needs
+    /// more givens.
+    pub fn ambiguous_cells(&self) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
+        self.empty_cells()
+            .map(move |(row, col)| ((row, col), self.digits(row, col)))
+            .filter(|&(_, mask)| mask.count_ones() > 1)
+    }
+
+    fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.board.cells().filter(|&(_, value)| value == 0).map(|(position, _)| position)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::{two_solutions, unsolvable};
+
+    #[test]
+    fn test_ambiguous_cells() {
+        let backbone = Backbone::new(&two_solutions(), 100).unwrap();
+        let both = digit_bit(5) | digit_bit(8);
+
+        assert_eq!((2, true), (backbone.solutions(), backbone.is_complete()));
+        assert_eq!(
+            vec!(((0, 2), both), ((0, 5), both), ((1, 2), both), ((1, 5), both)),
+            backbone.ambiguous_cells().collect::<Vec<_>>()
+        );
+        assert_eq!(None, backbone.fixed_cells().next());
+        assert_eq!(Some(1), backbone.fixed(0, 0));
+        assert_eq!(None, backbone.fixed(0, 2));
+    }
+
+    #[test]
+    fn test_fixed_cells() {
+        let mut board = two_solutions();
+        board.set(0, 2, 5);
+        board.set(2, 0, 0);
+        let backbone = Backbone::new(&board, 100).unwrap();
+
+        assert_eq!((1, true), (backbone.solutions(), backbone.is_complete()));
+        assert_eq!(
+            vec!(((0, 5), 8), ((1, 2), 8), ((1, 5), 5), ((2, 0), 6)),
+            backbone.fixed_cells().collect::<Vec<_>>()
+        );
+        assert_eq!(None, backbone.ambiguous_cells().next());
+    }
+
+    #[test]
+    fn test_limit_matching_solutions() {
+        let mut board = two_solutions();
+        board.set(0, 2, 5);
+        let unique = Backbone::new(&board, 1).unwrap();
+        assert_eq!((1, true), (unique.solutions(), unique.is_complete()));
+
+        let both = Backbone::new(&two_solutions(), 2).unwrap();
+        assert_eq!((2, true), (both.solutions(), both.is_complete()));
+
+        let first = Backbone::new(&two_solutions(), 1).unwrap();
+        assert_eq!((1, false), (first.solutions(), first.is_complete()));
+        assert_eq!(None, first.ambiguous_cells().next());
+    }
+
+    #[test]
+    fn test_limit() {
+        let board = Board::empty(Geometry::new(2, 2).unwrap());
+
+        let partial = Backbone::new(&board, 3).unwrap();
+        assert_eq!((3, false), (partial.solutions(), partial.is_complete()));
+
+        assert_eq!(Backbone::new(&board, 1), Backbone::new(&board, 0));
+
+        let full = Backbone::new(&board, 1000).unwrap();
+        assert_eq!((288, true), (full.solutions(), full.is_complete()));
+        assert_eq!(16, full.ambiguous_cells().count());
+        assert!(full.ambiguous_cells().all(|(_, mask)| mask == 0b1111));
+    }
+
+    #[test]
+    fn test_invalid_and_unsolvable() {
+        let mut board = two_solutions();
+        board.set(0, 2, 1);
+        assert!(matches!(Backbone::new(&board, 10), Err(SudokuError::DuplicateInUnit { .. })));
+
+        let board = unsolvable();
+        assert_eq!(Err(SudokuError::Unsolvable), Backbone::new(&board, 10));
+    }
+}
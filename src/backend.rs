@@ -0,0 +1,417 @@
+use std::str::FromStr;
+
+use crate::board::Board;
+use crate::dlx::DlxSolver;
+use crate::error::SudokuError;
+use crate::game::{find_empty, is_valid_board};
+use crate::parallel::ParallelSolver;
+use crate::sat::SatSolver;
+use crate::solver::{SolveResult, Solver};
+
+/// Anything that can solve and count the solutions of a board, so the
+/// algorithm can be picked at runtime.
+pub trait SolverBackend {
+    /// A short name for reports, the same one `Backend` parses.
+    fn name(&self) -> &'static str;
+
+    fn solve(&mut self, board: &Board) -> SolveResult;
+
+    /// Counts the solutions of a board, stopping once `limit` have been
+    /// found. Invalid boards have no solutions.
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize;
+
+    /// Whether a board keeps the rules this backend solves under, the classic
+    /// ones unless it says otherwise.
+    fn is_valid_board(&self, board: &Board) -> bool {
+        is_valid_board(board)
+    }
+}
+
+impl<'a> SolverBackend for Solver<'a> {
+    fn name(&self) -> &'static str {
+        "backtracking"
+    }
+
+    fn solve(&mut self, board: &Board) -> SolveResult {
+        Solver::solve(self, board)
+    }
+
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        Solver::count_solutions(self, board, limit)
+    }
+
+    fn is_valid_board(&self, board: &Board) -> bool {
+        Solver::is_valid_board(self, board)
+    }
+}
+
+impl SolverBackend for DlxSolver {
+    fn name(&self) -> &'static str {
+        "dlx"
+    }
+
+    fn solve(&mut self, board: &Board) -> SolveResult {
+        DlxSolver::solve(self, board)
+    }
+
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        DlxSolver::count_solutions(self, board, limit)
+    }
+}
+
+impl SolverBackend for SatSolver {
+    fn name(&self) -> &'static str {
+        "sat"
+    }
+
+    fn solve(&mut self, board: &Board) -> SolveResult {
+        SatSolver::solve(self, board)
+    }
+
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        SatSolver::count_solutions(self, board, limit)
+    }
+}
+
+impl SolverBackend for ParallelSolver {
+    fn name(&self) -> &'static str {
+        "parallel"
+    }
+
+    fn solve(&mut self, board: &Board) -> SolveResult {
+        ParallelSolver::solve(self, board)
+    }
+
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        ParallelSolver::count_solutions(self, board, limit)
+    }
+}
+
+impl<T: SolverBackend + ?Sized> SolverBackend for Box<T> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn solve(&mut self, board: &Board) -> SolveResult {
+        (**self).solve(board)
+    }
+
+    fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        (**self).count_solutions(board, limit)
+    }
+
+    fn is_valid_board(&self, board: &Board) -> bool {
+        (**self).is_valid_board(board)
+    }
+}
+
+/// The built in backends, each with its default options.
+///
+/// ```
+/// use sudoku_solver::{Backend, Board};
+///
+/// let mut backend = "dlx".parse::<Backend>().unwrap().build();
+/// let solved = backend.solve(&Board::new()).solution.unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Backtracking,
+    Dlx,
+    Sat,
+    Parallel,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [Backend::Backtracking, Backend::Dlx, Backend::Sat, Backend::Parallel];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Backtracking => "backtracking",
+            Backend::Dlx => "dlx",
+            Backend::Sat => "sat",
+            Backend::Parallel => "parallel",
+        }
+    }
+
+    pub fn build(self) -> Box<dyn SolverBackend> {
+        match self {
+            Backend::Backtracking => Box::new(Solver::new()),
+            Backend::Dlx => Box::new(DlxSolver::new()),
+            Backend::Sat => Box::new(SatSolver::new()),
+            Backend::Parallel => Box::new(ParallelSolver::new()),
+        }
+    }
+}
+
+impl FromStr for Backend {
+    type Err = SudokuError;
+
+    fn from_str(name: &str) -> Result<Backend, SudokuError> {
+        Backend::ALL
+            .iter()
+            .copied()
+            .find(|backend| backend.name() == name)
+            .ok_or_else(|| SudokuError::UnknownBackend { name: name.to_string() })
+    }
+}
+
+/// Runs two backends on the same board and fails with
+/// `SudokuError::BackendMismatch` when they don't agree on the number of
+/// solutions, or when either one's solution doesn't fit the board. Puzzles
+/// with several solutions may be solved differently, so the solutions
+/// themselves are only compared when there is just one.
+///
+/// ```
+/// use sudoku_solver::{Backend, Board, CrossCheck};
+///
+/// let mut check = CrossCheck::new(Backend::Dlx.build(), Backend::Sat.build());
+///
+/// assert_eq!(Ok(2), check.check(&Board::new()));
+/// ```
+pub struct CrossCheck<'a> {
+    first: Box<dyn SolverBackend + 'a>,
+    second: Box<dyn SolverBackend + 'a>,
+    count_limit: usize,
+}
+
+impl<'a> CrossCheck<'a> {
+    pub fn new<A: SolverBackend + 'a, B: SolverBackend + 'a>(first: A, second: B) -> CrossCheck<'a> {
+        CrossCheck {
+            first: Box::new(first),
+            second: Box::new(second),
+            count_limit: 2,
+        }
+    }
+
+    /// How many solutions to count before stopping, 2 by default which is
+    /// enough to tell a unique puzzle from one that isn't.
+    pub fn count_limit(mut self, limit: usize) -> CrossCheck<'a> {
+        self.count_limit = limit.max(1);
+        self
+    }
+
+    /// Checks one board, returning the number of solutions both backends
+    /// counted up to the limit. A backend that runs out of budget or is
+    /// cancelled fails the check with its own error instead of a mismatch.
+    pub fn check(&mut self, board: &Board) -> Result<usize, SudokuError> {
+        let (first, second) = (self.first.name(), self.second.name());
+        let mismatch = |reason: String| Err(SudokuError::BackendMismatch { first, second, reason });
+
+        let count = self.first.count_solutions(board, self.count_limit);
+        let other = self.second.count_solutions(board, self.count_limit);
+        if count != other {
+            return mismatch(format!("counted {} and {} solutions", count, other));
+        }
+
+        let solutions = (self.first.solve(board).solution, self.second.solve(board).solution);
+        for error in [&solutions.0, &solutions.1].iter().filter_map(|solution| solution.as_ref().err()) {
+            if let SudokuError::BudgetExhausted { .. } | SudokuError::Cancelled { .. } = error {
+                return Err(error.clone());
+            }
+        }
+
+        match solutions {
+            (Ok(solution), Ok(other)) => {
+                for (backend, solution) in [(&self.first, &solution), (&self.second, &other)].iter() {
+                    if !fits(backend.as_ref(), board, solution) {
+                        return mismatch(format!("{} returned a board that doesn't solve the puzzle", backend.name()));
+                    }
+                }
+
+                if count == 0 {
+                    mismatch("both solved a board neither counted any solutions for".to_string())
+                } else if count == 1 && solution != other {
+                    mismatch("found different solutions to a puzzle with only one".to_string())
+                } else {
+                    Ok(count)
+                }
+            }
+            (Ok(_), Err(error)) => mismatch(format!("{} failed where {} solved it: {}", second, first, error)),
+            (Err(error), Ok(_)) => mismatch(format!("{} failed where {} solved it: {}", first, second, error)),
+            (Err(error), Err(other)) => {
+                if error != other {
+                    mismatch(format!("failed with different errors: {} and {}", error, other))
+                } else if count > 0 {
+                    mismatch(format!("both failed on a board with solutions: {}", error))
+                } else {
+                    Ok(0)
+                }
+            }
+        }
+    }
+}
+
+// whether a solution is a full board that keeps every given and the rules
+// of the backend that found it
+fn fits(backend: &dyn SolverBackend, board: &Board, solution: &Board) -> bool {
+    solution.geometry() == board.geometry()
+        && find_empty(solution).is_none()
+        && backend.is_valid_board(solution)
+        && board.cells().all(|(position, value)| value == 0 || solution[position] == value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::constraints::Rules;
+    use crate::fixtures::two_solutions;
+    use crate::solver::SolveStats;
+
+    // a backend that always hands back the same answer
+    struct Fixed {
+        solution: Result<Board, SudokuError>,
+        count: usize,
+    }
+
+    impl SolverBackend for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        fn solve(&mut self, _board: &Board) -> SolveResult {
+            SolveResult { solution: self.solution.clone(), stats: SolveStats::default() }
+        }
+
+        fn count_solutions(&mut self, _board: &Board, limit: usize) -> usize {
+            self.count.min(limit)
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        for &backend in &Backend::ALL {
+            assert_eq!(Ok(backend), backend.name().parse());
+            assert_eq!(backend.name(), backend.build().name());
+        }
+
+        assert_eq!(Err(SudokuError::UnknownBackend { name: "bogo".to_string() }), "bogo".parse::<Backend>());
+    }
+
+    macro_rules! test_backends_agree {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (board, expected) = $value;
+
+                    for &first in &Backend::ALL {
+                        for &second in &Backend::ALL {
+                            let mut check = CrossCheck::new(first.build(), second.build()).count_limit(10);
+                            assert_eq!(expected, check.check(&board));
+                        }
+                    }
+                }
+            )*
+        }
+    }
+
+    test_backends_agree! {
+        two_solution_board: (two_solutions(), Ok(2)),
+        six_by_six: (Board::empty(Geometry::new(2, 3).unwrap()), Ok(10)),
+        invalid_board: ({
+            let mut board = two_solutions();
+            board.set(0, 2, 1);
+            board
+        }, Ok(0)),
+    }
+
+    #[test]
+    fn test_count_mismatch() {
+        let board = two_solutions();
+        let mut solution = board.clone();
+        solution.set(0, 2, 5);
+        solution.set(0, 5, 8);
+        solution.set(1, 2, 8);
+        solution.set(1, 5, 5);
+
+        let mut check = CrossCheck::new(DlxSolver::new(), Fixed { solution: Ok(solution), count: 1 });
+        assert_eq!(
+            Err(SudokuError::BackendMismatch { first: "dlx", second: "fixed", reason: "counted 2 and 1 solutions".to_string() }),
+            check.check(&board)
+        );
+    }
+
+    #[test]
+    fn test_solution_mismatch() {
+        let board = two_solutions();
+        let mut check = CrossCheck::new(SatSolver::new(), Fixed { solution: Ok(board.clone()), count: 2 });
+        assert_eq!(
+            Err(SudokuError::BackendMismatch {
+                first: "sat",
+                second: "fixed",
+                reason: "fixed returned a board that doesn't solve the puzzle".to_string(),
+            }),
+            check.check(&board)
+        );
+
+        let mut check = CrossCheck::new(Solver::new(), Fixed { solution: Err(SudokuError::Unsolvable), count: 2 });
+        assert_eq!(
+            Err(SudokuError::BackendMismatch {
+                first: "backtracking",
+                second: "fixed",
+                reason: "fixed failed where backtracking solved it: puzzle is unsolvable".to_string(),
+            }),
+            check.check(&board)
+        );
+    }
+
+    #[test]
+    fn test_solutions_checked_against_backend_rules() {
+        // rows and columns only, so solutions are free to repeat digits in a box
+        let geometry = Geometry::new(2, 2).unwrap();
+        let latin = || {
+            (0..4).fold(Rules::new(geometry), |rules, i| {
+                rules.unit((0..4).map(|j| (i, j)).collect()).unit((0..4).map(|j| (j, i)).collect())
+            })
+        };
+        let board = Board::empty(geometry);
+
+        let mut check = CrossCheck::new(Solver::new().rules(latin()), Solver::new().rules(latin()));
+        assert_eq!(Ok(2), check.check(&board));
+
+        let solution = Solver::new().rules(latin()).solve(&board).solution.unwrap();
+        assert!(!is_valid_board(&solution));
+        let mut check = CrossCheck::new(Fixed { solution: Ok(solution), count: 2 }, Solver::new().rules(latin()));
+        assert_eq!(
+            Err(SudokuError::BackendMismatch {
+                first: "fixed",
+                second: "backtracking",
+                reason: "fixed returned a board that doesn't solve the puzzle".to_string(),
+            }),
+            check.check(&board)
+        );
+    }
+
+    #[test]
+    fn test_different_unique_solutions() {
+        let solution = DlxSolver::new().solve(&two_solutions()).solution.unwrap();
+
+        // swapping two rows keeps the board valid but changes the solution
+        let mut other = solution.clone();
+        for col in 0..9 {
+            other.set(3, col, solution[(4, col)]);
+            other.set(4, col, solution[(3, col)]);
+        }
+        let mut board = Board::new();
+        board.set(0, 0, solution[(0, 0)]);
+
+        let mut check = CrossCheck::new(Fixed { solution: Ok(solution), count: 1 }, Fixed { solution: Ok(other), count: 1 });
+        assert_eq!(
+            Err(SudokuError::BackendMismatch {
+                first: "fixed",
+                second: "fixed",
+                reason: "found different solutions to a puzzle with only one".to_string(),
+            }),
+            check.check(&board)
+        );
+    }
+
+    #[test]
+    fn test_budget_is_not_a_mismatch() {
+        let mut check = CrossCheck::new(DlxSolver::new(), Solver::new().node_limit(5));
+        let result = check.check(&Board::empty(Geometry::new(2, 3).unwrap()));
+
+        assert!(matches!(result, Err(SudokuError::BudgetExhausted { .. })));
+    }
+}
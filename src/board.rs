@@ -0,0 +1,439 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::error::SudokuError;
+
+/// The side of a classic board.
+pub const SIZE: usize = 9;
+/// The side of a box on a classic board.
+pub const BOX_SIZE: usize = 3;
+/// The largest supported side, candidate masks hold one bit per digit.
+pub const MAX_SIZE: usize = 32;
+
+/// The shape of a board, described by the rows and columns of one box. A
+/// board is as many cells across as there are cells in a box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Geometry {
+    box_rows: usize,
+    box_cols: usize,
+}
+
+impl Geometry {
+    pub fn new(box_rows: usize, box_cols: usize) -> Result<Geometry, SudokuError> {
+        if box_rows == 0 || box_cols == 0 || box_rows.checked_mul(box_cols).is_none_or(|size| size > MAX_SIZE) {
+            return Err(SudokuError::UnsupportedGeometry { box_rows, box_cols });
+        }
+
+        Ok(Geometry { box_rows, box_cols })
+    }
+
+    /// 9 x 9 with 3 x 3 boxes.
+    pub fn classic() -> Geometry {
+        Geometry {
+            box_rows: BOX_SIZE,
+            box_cols: BOX_SIZE,
+        }
+    }
+
+    /// The usual geometry for a board of the given side, the boxes are as
+    /// close to square as possible and wider than they are tall, so 6 x 6 gets
+    /// 2 x 3 boxes and 12 x 12 gets 3 x 4 boxes. Sides such as 5 or 7 that
+    /// could only have boxes one row tall are rejected, use `Geometry::new`
+    /// to build those on purpose.
+    pub fn for_size(size: usize) -> Result<Geometry, SudokuError> {
+        if size == 0 || size > MAX_SIZE {
+            return Err(SudokuError::WrongDimensions { rows: size, cols: size });
+        }
+
+        let box_rows = (1..=size).filter(|&i| size % i == 0 && i * i <= size).max().unwrap();
+        if box_rows == 1 {
+            return Err(SudokuError::WrongDimensions { rows: size, cols: size });
+        }
+
+        Geometry::new(box_rows, size / box_rows)
+    }
+
+    pub fn box_rows(&self) -> usize {
+        self.box_rows
+    }
+
+    pub fn box_cols(&self) -> usize {
+        self.box_cols
+    }
+
+    /// The number of rows, columns, boxes and digits.
+    pub fn size(&self) -> usize {
+        self.box_rows * self.box_cols
+    }
+
+    /// A candidate mask with a bit set for every digit.
+    pub fn all_digits(&self) -> u32 {
+        u32::MAX >> (32 - self.size())
+    }
+}
+
+impl Default for Geometry {
+    fn default() -> Geometry {
+        Geometry::classic()
+    }
+}
+
+/// A row, column or box that must not repeat a value, or another unit added
+/// to a set of `Rules`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+    Custom(usize),
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Unit::Row(index) => write!(f, "row {}", index),
+            Unit::Column(index) => write!(f, "column {}", index),
+            Unit::Box(index) => write!(f, "box {}", index),
+            Unit::Custom(index) => write!(f, "unit {}", index),
+        }
+    }
+}
+
+/// A square grid where 0 marks an empty cell, 9 x 9 unless built with
+/// another `Geometry`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    geometry: Geometry,
+    cells: Vec<u8>,
+}
+
+impl Board {
+    /// An empty 9 x 9 board.
+    pub fn new() -> Board {
+        Board::empty(Geometry::classic())
+    }
+
+    /// An empty board of any geometry.
+    pub fn empty(geometry: Geometry) -> Board {
+        Board {
+            geometry,
+            cells: vec![0; geometry.size() * geometry.size()],
+        }
+    }
+
+    /// Builds a board from rows. The rows must form a square and the
+    /// geometry is picked with `Geometry::for_size`.
+    pub fn from_rows(rows: &[Vec<u8>]) -> Result<Board, SudokuError> {
+        let size = rows.len();
+        let cols = rows.iter().map(|row| row.len()).find(|&len| len != size);
+
+        match cols {
+            Some(cols) => Err(SudokuError::WrongDimensions { rows: size, cols }),
+            None => Board::from_rows_with(Geometry::for_size(size)?, rows),
+        }
+    }
+
+    /// Builds a board from rows that must match the geometry.
+    pub fn from_rows_with(geometry: Geometry, rows: &[Vec<u8>]) -> Result<Board, SudokuError> {
+        let size = geometry.size();
+        let cols = rows.iter().map(|row| row.len()).find(|&len| len != size);
+
+        if rows.len() != size || cols.is_some() {
+            return Err(SudokuError::WrongDimensions {
+                rows: rows.len(),
+                cols: cols.unwrap_or(size),
+            });
+        }
+
+        Ok(Board {
+            geometry,
+            cells: rows.concat(),
+        })
+    }
+
+    /// Builds a board from values in row-major order, the geometry is picked
+    /// from the number of values. A slice that isn't a square number of
+    /// values is reported as a single row of that many values.
+    pub fn from_cells(cells: &[u8]) -> Result<Board, SudokuError> {
+        let size = (0..=MAX_SIZE).find(|&i| i * i >= cells.len()).unwrap_or(0);
+
+        if size * size != cells.len() {
+            return Err(SudokuError::WrongDimensions {
+                rows: 1,
+                cols: cells.len(),
+            });
+        }
+
+        Ok(Board {
+            geometry: Geometry::for_size(size)?,
+            cells: cells.to_vec(),
+        })
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// The number of rows, columns, boxes and digits.
+    pub fn size(&self) -> usize {
+        self.geometry.size()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> u8 {
+        self[(row, col)]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: u8) {
+        self[(row, col)] = value;
+    }
+
+    pub fn row(&self, row: usize) -> &[u8] {
+        let size = self.size();
+        &self.cells[row * size..(row + 1) * size]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        self.cells.chunks(self.size())
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<u8>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+
+    /// Every cell with its value in row-major order.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        let size = self.size();
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, &value)| ((i / size, i % size), value))
+    }
+
+    pub fn row_cells(&self, row: usize) -> impl Iterator<Item = (usize, usize)> {
+        (0..self.size()).map(move |col| (row, col))
+    }
+
+    pub fn column_cells(&self, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        (0..self.size()).map(move |row| (row, col))
+    }
+
+    /// Boxes are numbered left to right, top to bottom. Panics if there is no
+    /// box with that index.
+    pub fn box_cells(&self, index: usize) -> impl Iterator<Item = (usize, usize)> {
+        let size = self.size();
+        assert!(index < size, "box {} is off a {} x {} board", index, size, size);
+
+        let Geometry { box_rows, box_cols } = self.geometry;
+        // there are as many boxes across the board as there are rows in a box
+        let row = index / box_rows * box_rows;
+        let col = index % box_rows * box_cols;
+
+        (0..self.size()).map(move |i| (row + i / box_cols, col + i % box_cols))
+    }
+
+    pub fn box_index(&self, row: usize, col: usize) -> usize {
+        let Geometry { box_rows, box_cols } = self.geometry;
+
+        row / box_rows * box_rows + col / box_cols
+    }
+
+    pub fn units(&self) -> impl Iterator<Item = Unit> {
+        let size = self.size();
+
+        (0..size)
+            .map(Unit::Row)
+            .chain((0..size).map(Unit::Column))
+            .chain((0..size).map(Unit::Box))
+    }
+
+    /// The cells of a row, column or box. The board doesn't know the cells
+    /// of custom units, those come from `Rules::units`.
+    pub fn unit_cells(&self, unit: Unit) -> Vec<(usize, usize)> {
+        match unit {
+            Unit::Row(row) => self.row_cells(row).collect(),
+            Unit::Column(col) => self.column_cells(col).collect(),
+            Unit::Box(index) => self.box_cells(index).collect(),
+            Unit::Custom(_) => Vec::new(),
+        }
+    }
+
+    /// Every other cell sharing a row, column or box with the given cell.
+    pub fn peers(&self, row: usize, col: usize) -> impl Iterator<Item = (usize, usize)> {
+        let in_row = self.row_cells(row).filter(move |&(_, c)| c != col);
+        let in_column = self.column_cells(col).filter(move |&(r, _)| r != row);
+        let in_box = self
+            .box_cells(self.box_index(row, col))
+            .filter(move |&(r, c)| r != row && c != col);
+
+        in_row.chain(in_column).chain(in_box)
+    }
+}
+
+impl Default for Board {
+    fn default() -> Board {
+        Board::new()
+    }
+}
+
+impl Index<(usize, usize)> for Board {
+    type Output = u8;
+
+    fn index(&self, (row, col): (usize, usize)) -> &u8 {
+        let size = self.size();
+        assert!(row < size && col < size, "cell ({}, {}) is off a {} x {} board", row, col, size, size);
+
+        &self.cells[row * size + col]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Board {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut u8 {
+        let size = self.size();
+        assert!(row < size && col < size, "cell ({}, {}) is off a {} x {} board", row, col, size, size);
+
+        &mut self.cells[row * size + col]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_rows_wrong_shape() {
+        let wrong_dimensions = |rows, cols| Err(SudokuError::WrongDimensions { rows, cols });
+
+        assert_eq!(wrong_dimensions(0, 0), Board::from_rows(&[]));
+        assert_eq!(wrong_dimensions(8, 9), Board::from_rows(&vec!(vec!(0; 9); 8)));
+        assert_eq!(wrong_dimensions(9, 8), Board::from_rows(&vec!(vec!(0; 8); 9)));
+
+        let mut ragged = vec!(vec!(0; 9); 9);
+        ragged[4].push(0);
+        assert_eq!(wrong_dimensions(9, 10), Board::from_rows(&ragged));
+    }
+
+    #[test]
+    fn test_from_cells() {
+        assert_eq!(
+            Err(SudokuError::WrongDimensions { rows: 1, cols: 80 }),
+            Board::from_cells(&[0; 80])
+        );
+
+        let cells: Vec<u8> = (0..81).map(|i| (i % 10) as u8).collect();
+        let board = Board::from_cells(&cells).unwrap();
+        assert_eq!(3, board.get(0, 3));
+        assert_eq!(1, board[(1, 2)]);
+        assert_eq!(cells, board.to_rows().concat());
+    }
+
+    #[test]
+    fn test_box_cells() {
+        let cells: Vec<(usize, usize)> = Board::new().box_cells(5).collect();
+        assert_eq!(
+            vec!((3, 6), (3, 7), (3, 8), (4, 6), (4, 7), (4, 8), (5, 6), (5, 7), (5, 8)),
+            cells
+        );
+        assert_eq!(5, Board::new().box_index(4, 7));
+    }
+
+    #[test]
+    fn test_units() {
+        let board = Board::new();
+
+        assert_eq!(27, board.units().count());
+        assert_eq!(vec!((0, 3), (1, 3), (2, 3)), board.unit_cells(Unit::Column(3))[..3].to_vec());
+        assert_eq!(board.box_cells(4).collect::<Vec<_>>(), board.unit_cells(Unit::Box(4)));
+    }
+
+    #[test]
+    fn test_peers() {
+        let board = Board::new();
+        let peers: Vec<(usize, usize)> = board.peers(4, 4).collect();
+
+        assert_eq!(20, peers.len());
+        assert!(!peers.contains(&(4, 4)));
+        assert!(peers.contains(&(3, 5)));
+        assert!(peers.contains(&(0, 4)));
+        assert!(!peers.contains(&(2, 2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "cell (0, 10) is off a 9 x 9 board")]
+    fn test_get_off_board() {
+        Board::new().get(0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "box 9 is off a 9 x 9 board")]
+    fn test_box_off_board() {
+        Board::new().box_cells(9).count();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_off_board() {
+        Board::new().set(9, 0, 1);
+    }
+
+    #[test]
+    fn test_geometry_for_size() {
+        let shape = |size| Geometry::for_size(size).map(|g| (g.box_rows(), g.box_cols()));
+
+        assert_eq!(Ok((2, 2)), shape(4));
+        assert_eq!(Ok((2, 3)), shape(6));
+        assert_eq!(Ok((3, 3)), shape(9));
+        assert_eq!(Ok((3, 4)), shape(12));
+        assert_eq!(Ok((4, 4)), shape(16));
+        assert_eq!(Ok((5, 5)), shape(25));
+        assert!(Geometry::for_size(0).is_err());
+        assert!(Geometry::for_size(33).is_err());
+        assert_eq!(Err(SudokuError::WrongDimensions { rows: 5, cols: 5 }), Geometry::for_size(5));
+        assert!(Geometry::for_size(7).is_err());
+        assert!(Board::from_rows(&vec!(vec!(0; 5); 5)).is_err());
+        assert_eq!(
+            Err(SudokuError::UnsupportedGeometry { box_rows: 0, box_cols: 3 }),
+            Geometry::new(0, 3)
+        );
+        assert_eq!(
+            Err(SudokuError::UnsupportedGeometry { box_rows: usize::MAX, box_cols: 2 }),
+            Geometry::new(usize::MAX, 2)
+        );
+        assert_eq!(0b1111, Geometry::for_size(4).unwrap().all_digits());
+        assert_eq!(u32::MAX, Geometry::new(4, 8).unwrap().all_digits());
+    }
+
+    #[test]
+    fn test_from_rows_other_sizes() {
+        let board = Board::from_rows(&vec!(vec!(0; 6); 6)).unwrap();
+        assert_eq!(Geometry::new(2, 3).unwrap(), board.geometry());
+
+        let tall = Geometry::new(3, 2).unwrap();
+        let board = Board::from_rows_with(tall, &vec!(vec!(0; 6); 6)).unwrap();
+        assert_eq!(tall, board.geometry());
+
+        assert_eq!(
+            Err(SudokuError::WrongDimensions { rows: 6, cols: 6 }),
+            Board::from_rows_with(Geometry::classic(), &vec!(vec!(0; 6); 6))
+        );
+        assert_eq!(16, Board::from_cells(&[0; 256]).unwrap().size());
+    }
+
+    #[test]
+    fn test_rectangular_boxes() {
+        let board = Board::empty(Geometry::new(2, 3).unwrap());
+
+        assert_eq!(
+            vec!((2, 3), (2, 4), (2, 5), (3, 3), (3, 4), (3, 5)),
+            board.box_cells(3).collect::<Vec<_>>()
+        );
+        assert_eq!(3, board.box_index(3, 5));
+        assert_eq!(4, board.box_index(5, 0));
+        assert_eq!(18, board.units().count());
+        assert_eq!(5 + 5 + 2, board.peers(0, 0).count());
+
+        for index in 0..6 {
+            assert!(board.box_cells(index).all(|(row, col)| board.box_index(row, col) == index));
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::board::Board;
+use crate::constraints::Rules;
+use crate::error::SudokuError;
+
+/// The mask bit for a digit, digit `n` is bit `n - 1`.
+pub fn digit_bit(digit: u8) -> u32 {
+    1 << (digit - 1)
+}
+
+/// The digits set in a candidate mask in ascending order.
+pub fn digits(mask: u32) -> Digits {
+    Digits(mask)
+}
+
+#[derive(Clone, Debug)]
+pub struct Digits(u32);
+
+impl Iterator for Digits {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let digit = self.0.trailing_zeros() as u8 + 1;
+        self.0 &= self.0 - 1;
+
+        Some(digit)
+    }
+}
+
+/// Pencil marks for every empty cell, kept up to date as digits are placed
+/// and removed.
+#[derive(Clone, Debug)]
+pub struct Candidates {
+    board: Board,
+    rules: Arc<Rules>,
+    // the digits already used in each unit of the rules
+    used: Vec<u32>,
+}
+
+impl Candidates {
+    /// Pencil marks under the classic rules.
+    pub fn new(board: &Board) -> Result<Candidates, SudokuError> {
+        Candidates::with_rules(board, Arc::new(Rules::classic(board.geometry())))
+    }
+
+    /// Pencil marks under any set of rules for the board's geometry.
+    pub fn with_rules(board: &Board, rules: Arc<Rules>) -> Result<Candidates, SudokuError> {
+        rules.validate(board)?;
+
+        let mut candidates = Candidates {
+            board: Board::empty(board.geometry()),
+            used: vec![0; rules.unit_count()],
+            rules,
+        };
+
+        for ((row, col), value) in board.cells() {
+            if value != 0 {
+                candidates.fill(row, col, value);
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The legal digits for a cell as a mask, filled cells have none.
+    pub fn get(&self, row: usize, col: usize) -> u32 {
+        if self.board[(row, col)] != 0 {
+            return 0;
+        }
+
+        let used = self.rules.cell_units(row, col).iter().fold(0, |used, &unit| used | self.used[unit]);
+        let mask = self.board.geometry().all_digits() & !used;
+
+        if !self.rules.has_constraints() {
+            return mask;
+        }
+
+        digits(mask)
+            .filter(|&digit| self.rules.allows(&self.board, row, col, digit))
+            .fold(0, |mask, digit| mask | digit_bit(digit))
+    }
+
+    pub fn count(&self, row: usize, col: usize) -> u32 {
+        self.get(row, col).count_ones()
+    }
+
+    pub fn contains(&self, row: usize, col: usize, digit: u8) -> bool {
+        (1..=self.board.size()).contains(&(digit as usize)) && self.get(row, col) & digit_bit(digit) != 0
+    }
+
+    /// Every empty cell with its candidate mask in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), u32)> + '_ {
+        self.board
+            .cells()
+            .filter(|&(_, value)| value == 0)
+            .map(move |((row, col), _)| ((row, col), self.get(row, col)))
+    }
+
+    /// Places a digit in an empty cell, it must be one of the cell's candidates.
+    pub fn place(&mut self, row: usize, col: usize, digit: u8) -> Result<(), SudokuError> {
+        if !self.contains(row, col, digit) {
+            return Err(SudokuError::IllegalMove { row, col, value: digit });
+        }
+
+        self.fill(row, col, digit);
+
+        Ok(())
+    }
+
+    /// Clears a cell, returning the digit that was there.
+    pub fn remove(&mut self, row: usize, col: usize) -> Option<u8> {
+        let digit = self.board[(row, col)];
+        if digit == 0 {
+            return None;
+        }
+
+        let bit = !digit_bit(digit);
+        for &unit in self.rules.cell_units(row, col) {
+            self.used[unit] &= bit;
+        }
+        self.board.set(row, col, 0);
+
+        Some(digit)
+    }
+
+    // places a digit without checking it is a candidate, the solver only
+    // ever offers digits taken from the cell's mask
+    pub(crate) fn fill(&mut self, row: usize, col: usize, digit: u8) {
+        let bit = digit_bit(digit);
+        for &unit in self.rules.cell_units(row, col) {
+            self.used[unit] |= bit;
+        }
+        self.board.set(row, col, digit);
+    }
+}
+
+// hands every solution to `visit` until `stop` is set, by the visitor or from
+// another thread, filling the cell with the fewest digits left first since
+// callers only care which solutions there are, not the order they come in
+pub(crate) fn each_solution<F: FnMut(&Board)>(candidates: &mut Candidates, stop: &AtomicBool, visit: &mut F) {
+    if stop.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let ((row, col), mask) = match candidates.iter().min_by_key(|&(_, mask)| mask.count_ones()) {
+        Some(cell) => cell,
+        None => {
+            visit(candidates.board());
+            return;
+        }
+    };
+
+    for digit in digits(mask) {
+        candidates.fill(row, col, digit);
+        each_solution(candidates, stop, visit);
+        candidates.remove(row, col);
+
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+    }
+}
+
+// the rules can't be compared, two sets of marks are equal when the same
+// digits are placed and used in the same units
+impl PartialEq for Candidates {
+    fn eq(&self, other: &Candidates) -> bool {
+        self.board == other.board && self.used == other.used
+    }
+}
+
+impl Eq for Candidates {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::puzzle;
+    use crate::game::is_valid_move;
+
+    fn assert_matches_board(candidates: &Candidates) {
+        let board = candidates.board();
+
+        for ((row, col), value) in board.cells() {
+            let expected: Vec<u8> = if value == 0 {
+                (1..=board.size() as u8).filter(|&i| is_valid_move(board, &(row, col), i)).collect()
+            } else {
+                Vec::new()
+            };
+
+            assert_eq!(expected, digits(candidates.get(row, col)).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(vec!(1, 4, 9), digits(0b1_0000_1001).collect::<Vec<u8>>());
+        assert_eq!(9, digits(Geometry::classic().all_digits()).count());
+        assert_eq!(None, digits(0).next());
+    }
+
+    #[test]
+    fn test_new() {
+        let candidates = Candidates::new(&puzzle()).unwrap();
+
+        assert_eq!(vec!(1, 5, 6, 8), digits(candidates.get(0, 0)).collect::<Vec<u8>>());
+        assert_eq!(0, candidates.get(0, 5));
+        assert_eq!(4, candidates.count(0, 0));
+        assert_eq!(81 - 24, candidates.iter().count());
+        assert_matches_board(&candidates);
+    }
+
+    #[test]
+    fn test_new_invalid_board() {
+        let mut board = puzzle();
+        board.set(0, 0, 2);
+
+        assert!(Candidates::new(&board).is_err());
+    }
+
+    #[test]
+    fn test_place_and_remove() {
+        let mut candidates = Candidates::new(&puzzle()).unwrap();
+
+        candidates.place(0, 0, 8).unwrap();
+        assert!(!candidates.contains(0, 1, 8));
+        assert!(!candidates.contains(3, 0, 8));
+        assert!(!candidates.contains(2, 2, 8));
+        assert_matches_board(&candidates);
+
+        assert_eq!(Some(8), candidates.remove(0, 0));
+        assert_eq!(None, candidates.remove(0, 0));
+        assert_eq!(Candidates::new(&puzzle()).unwrap(), candidates);
+    }
+
+    #[test]
+    fn test_place_illegal() {
+        let mut candidates = Candidates::new(&puzzle()).unwrap();
+        let illegal = |row, col, value| Err(SudokuError::IllegalMove { row, col, value });
+
+        assert_eq!(illegal(0, 0, 7), candidates.place(0, 0, 7));
+        assert_eq!(illegal(0, 5, 1), candidates.place(0, 5, 1));
+        assert_eq!(illegal(0, 0, 0), candidates.place(0, 0, 0));
+        assert_eq!(illegal(0, 0, 10), candidates.place(0, 0, 10));
+        assert_eq!(Candidates::new(&puzzle()).unwrap(), candidates);
+    }
+
+    #[test]
+    fn test_six_by_six() {
+        let board = Board::from_rows(&[
+            vec!(1, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 2,),
+            vec!(0, 0, 0, 0, 0, 0,),
+            vec!(0, 3, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0,),
+        ]).unwrap();
+        let mut candidates = Candidates::new(&board).unwrap();
+
+        assert_eq!(vec!(2, 4, 5, 6), digits(candidates.get(0, 1)).collect::<Vec<u8>>());
+        assert_eq!(0b11_1110, candidates.get(5, 0));
+        assert!(!candidates.contains(0, 1, 7));
+
+        candidates.place(0, 1, 6).unwrap();
+        assert_matches_board(&candidates);
+    }
+}
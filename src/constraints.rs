@@ -0,0 +1,320 @@
+use std::fmt;
+
+use crate::board::{Board, Geometry, Unit};
+use crate::error::SudokuError;
+use crate::game::Conflict;
+
+/// A rule beyond the units, checked digit by digit.
+pub trait Constraint: fmt::Debug + Send + Sync {
+    /// Whether `digit` can go at `(row, col)` given the rest of the board.
+    /// The cell may already hold a digit, which should be ignored.
+    fn allows(&self, board: &Board, row: usize, col: usize, digit: u8) -> bool;
+}
+
+/// No two cells a chess knight's move apart hold the same digit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AntiKnight;
+
+impl Constraint for AntiKnight {
+    fn allows(&self, board: &Board, row: usize, col: usize, digit: u8) -> bool {
+        let size = board.size() as isize;
+        let moves = [(-2, -1), (-2, 1), (-1, -2), (-1, 2), (1, -2), (1, 2), (2, -1), (2, 1)];
+
+        moves.iter().all(|&(dr, dc)| {
+            let (r, c) = (row as isize + dr, col as isize + dc);
+            r < 0 || c < 0 || r >= size || c >= size || board[(r as usize, c as usize)] != digit
+        })
+    }
+}
+
+/// The rules of a puzzle: units whose cells must all hold different digits,
+/// and any extra constraints.
+///
+/// ```
+/// use sudoku_solver::{Board, Geometry, MinimumRemainingValues, Rules, Solver};
+///
+/// let solved = Solver::new()
+///     .rules(Rules::diagonal(Geometry::classic()))
+///     .cell_selector(MinimumRemainingValues)
+///     .solve(&Board::new())
+///     .solution
+///     .unwrap();
+///
+/// assert!(Rules::diagonal(Geometry::classic()).is_valid_board(&solved));
+/// ```
+#[derive(Debug)]
+pub struct Rules {
+    geometry: Geometry,
+    units: Vec<(Unit, Vec<(usize, usize)>)>,
+    // the units each cell is in, by cell index
+    cell_units: Vec<Vec<usize>>,
+    // the other cells sharing a unit with each cell, by cell index
+    peers: Vec<Vec<(usize, usize)>>,
+    constraints: Vec<Box<dyn Constraint>>,
+}
+
+impl Rules {
+    /// No units or constraints yet, for building up other rule sets.
+    pub fn new(geometry: Geometry) -> Rules {
+        Rules {
+            geometry,
+            units: Vec::new(),
+            cell_units: vec![Vec::new(); geometry.size() * geometry.size()],
+            peers: vec![Vec::new(); geometry.size() * geometry.size()],
+            constraints: Vec::new(),
+        }
+    }
+
+    /// The rows, columns and boxes of a normal sudoku.
+    pub fn classic(geometry: Geometry) -> Rules {
+        let board = Board::empty(geometry);
+        let mut rules = Rules::new(geometry);
+
+        for unit in board.units() {
+            rules.add_unit(unit, board.unit_cells(unit));
+        }
+
+        rules
+    }
+
+    /// The classic rules with both long diagonals as well.
+    pub fn diagonal(geometry: Geometry) -> Rules {
+        let size = geometry.size();
+
+        Rules::classic(geometry)
+            .unit((0..size).map(|i| (i, i)).collect())
+            .unit((0..size).map(|i| (i, size - 1 - i)).collect())
+    }
+
+    /// Adds a unit, named `Unit::Custom` with the number of custom units
+    /// before it. Panics if a cell is off the board.
+    pub fn unit(mut self, cells: Vec<(usize, usize)>) -> Rules {
+        let size = self.geometry.size();
+        assert!(cells.iter().all(|&(row, col)| row < size && col < size), "unit cell off the board");
+
+        let custom = self.units.iter().filter(|(unit, _)| matches!(unit, Unit::Custom(_))).count();
+        self.add_unit(Unit::Custom(custom), cells);
+        self
+    }
+
+    pub fn constraint<C: Constraint + 'static>(mut self, constraint: C) -> Rules {
+        self.constraints.push(Box::new(constraint));
+        self
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// Every unit and its cells, in the order they were added.
+    pub fn units(&self) -> impl Iterator<Item = (Unit, &[(usize, usize)])> {
+        self.units.iter().map(|(unit, cells)| (*unit, &cells[..]))
+    }
+
+    /// Every other cell sharing a unit with the given cell, in the order the
+    /// units were added.
+    pub fn peers(&self, row: usize, col: usize) -> &[(usize, usize)] {
+        &self.peers[row * self.geometry.size() + col]
+    }
+
+    pub(crate) fn unit_count(&self) -> usize {
+        self.units.len()
+    }
+
+    // the indexes of the units a cell is in
+    pub(crate) fn cell_units(&self, row: usize, col: usize) -> &[usize] {
+        &self.cell_units[row * self.geometry.size() + col]
+    }
+
+    pub(crate) fn has_constraints(&self) -> bool {
+        !self.constraints.is_empty()
+    }
+
+    // whether every extra constraint allows the digit
+    pub(crate) fn allows(&self, board: &Board, row: usize, col: usize, digit: u8) -> bool {
+        self.constraints.iter().all(|constraint| constraint.allows(board, row, col, digit))
+    }
+
+    /// Whether `digit` can go at `position` without repeating a value in any
+    /// of its units or breaking a constraint.
+    pub fn is_valid_move(&self, board: &Board, position: &(usize, usize), digit: u8) -> bool {
+        let (row, col) = *position;
+
+        let repeated = self.cell_units(row, col).iter().any(|&unit| self.units[unit].1.iter().any(|&pos| board[pos] == digit));
+
+        !repeated && self.allows(board, row, col, digit)
+    }
+
+    pub fn is_valid_board(&self, board: &Board) -> bool {
+        self.validate(board).is_ok()
+    }
+
+    /// Same checks as `is_valid_board` but reports the first problem found.
+    pub fn validate(&self, board: &Board) -> Result<(), SudokuError> {
+        if board.geometry() != self.geometry {
+            return Err(SudokuError::WrongDimensions { rows: board.size(), cols: board.size() });
+        }
+
+        match self.find_conflicts(board).into_iter().next() {
+            Some(conflict) => Err(conflict.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Every out of range value, then every repeated value in each unit, then
+    /// every digit that breaks a constraint.
+    pub fn find_conflicts(&self, board: &Board) -> Vec<Conflict> {
+        let size = board.size();
+
+        // make sure all numbers are between 0 and the size of the board
+        let mut conflicts: Vec<Conflict> = board
+            .cells()
+            .filter(|&(_, value)| value as usize > size)
+            .map(|((row, col), value)| Conflict::OutOfRange { row, col, value })
+            .collect();
+
+        // make sure all the values other than 0 are unique in every unit
+        for (unit, cells) in &self.units {
+            for value in find_duplicates(board, cells) {
+                conflicts.push(Conflict::Duplicate {
+                    unit: *unit,
+                    value,
+                    cells: cells.iter().cloned().filter(|&pos| board[pos] == value).collect(),
+                });
+            }
+        }
+
+        if self.has_constraints() {
+            for ((row, col), value) in board.cells() {
+                if value != 0 && value as usize <= size && !self.allows(board, row, col, value) {
+                    conflicts.push(Conflict::Constraint { row, col, value });
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    fn add_unit(&mut self, unit: Unit, cells: Vec<(usize, usize)>) {
+        let size = self.geometry.size();
+        for &(row, col) in &cells {
+            self.cell_units[row * size + col].push(self.units.len());
+
+            let peers = &mut self.peers[row * size + col];
+            for &other in &cells {
+                if other != (row, col) && !peers.contains(&other) {
+                    peers.push(other);
+                }
+            }
+        }
+
+        self.units.push((unit, cells));
+    }
+}
+
+// returns every value in range that appears more than once, in the order
+// they are first repeated
+fn find_duplicates(board: &Board, cells: &[(usize, usize)]) -> Vec<u8> {
+    let mut seen = vec![0; board.size() + 1];
+    let mut duplicates = Vec::new();
+
+    for &pos in cells {
+        let value = board[pos];
+        if value == 0 || value as usize > board.size() {
+            continue;
+        }
+
+        seen[value as usize] += 1;
+        if seen[value as usize] == 2 {
+            duplicates.push(value);
+        }
+    }
+
+    duplicates
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::solution;
+    use crate::game::is_valid_move;
+
+    #[test]
+    fn test_classic() {
+        let rules = Rules::classic(Geometry::classic());
+        let mut board = solution();
+        board.set(0, 0, 0);
+        board.set(4, 4, 0);
+
+        for ((row, col), _) in board.cells() {
+            for digit in 1..=9 {
+                let used = board[(row, col)] == digit || board.peers(row, col).any(|pos| board[pos] == digit);
+                assert_eq!(!used, rules.is_valid_move(&board, &(row, col), digit));
+                assert_eq!(!used, is_valid_move(&board, &(row, col), digit));
+            }
+        }
+
+        assert_eq!(27, rules.units().count());
+        assert_eq!(&[0, 9, 18], rules.cell_units(0, 0));
+        assert_eq!(board.peers(4, 4).collect::<Vec<_>>(), rules.peers(4, 4));
+        assert_eq!(Some((Unit::Box(8), board.box_cells(8).collect())), rules.units().last().map(|(unit, cells)| (unit, cells.to_vec())));
+    }
+
+    #[test]
+    fn test_custom_units() {
+        let rules = Rules::classic(Geometry::classic()).unit(vec!((0, 0), (8, 8)));
+        let mut board = Board::new();
+        board.set(8, 8, 4);
+
+        assert!(!rules.is_valid_move(&board, &(0, 0), 4));
+        assert!(rules.is_valid_move(&board, &(0, 1), 4));
+
+        board.set(0, 0, 4);
+        assert_eq!(
+            Err(SudokuError::DuplicateInUnit { unit: Unit::Custom(0), value: 4, cells: vec!((0, 0), (8, 8)) }),
+            rules.validate(&board)
+        );
+        assert_eq!("unit 0", Unit::Custom(0).to_string());
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let rules = Rules::diagonal(Geometry::classic());
+        let board = solution();
+
+        assert_eq!(29, rules.units().count());
+        assert_eq!(26, rules.peers(0, 0).len());
+        assert_eq!(32, rules.peers(4, 4).len());
+        assert_eq!(20, rules.peers(0, 1).len());
+        assert_eq!(Unit::Custom(1), rules.find_conflicts(&board).last().map(|conflict| match conflict {
+            Conflict::Duplicate { unit, .. } => *unit,
+            other => panic!("unexpected conflict {:?}", other),
+        }).unwrap());
+    }
+
+    #[test]
+    fn test_anti_knight() {
+        let rules = Rules::new(Geometry::classic()).constraint(AntiKnight);
+        let mut board = Board::new();
+        board.set(4, 4, 5);
+
+        assert!(!rules.is_valid_move(&board, &(2, 3), 5));
+        assert!(!rules.is_valid_move(&board, &(5, 6), 5));
+        assert!(rules.is_valid_move(&board, &(4, 5), 5));
+        assert!(rules.is_valid_move(&board, &(2, 3), 4));
+
+        board.set(6, 5, 5);
+        assert_eq!(
+            vec!(Conflict::Constraint { row: 4, col: 4, value: 5 }, Conflict::Constraint { row: 6, col: 5, value: 5 }),
+            rules.find_conflicts(&board)
+        );
+        assert_eq!(Err(SudokuError::IllegalMove { row: 4, col: 4, value: 5 }), rules.validate(&board));
+    }
+
+    #[test]
+    fn test_wrong_geometry() {
+        let rules = Rules::classic(Geometry::new(2, 2).unwrap());
+
+        assert_eq!(Err(SudokuError::WrongDimensions { rows: 9, cols: 9 }), rules.validate(&Board::new()));
+    }
+}
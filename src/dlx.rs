@@ -0,0 +1,456 @@
+use std::time::Instant;
+
+use crate::board::Board;
+use crate::candidates::{digits, Candidates};
+use crate::error::SudokuError;
+use crate::solver::{Budget, SolveResult, SolveStats};
+
+// the header every column header hangs off
+const ROOT: usize = 0;
+
+/// An exact cover solver using Knuth's Algorithm X with dancing links. Each
+/// cell, and each digit in each row, column and box, is a constraint that
+/// exactly one placement has to cover.
+///
+/// ```
+/// use sudoku_solver::{Board, DlxSolver};
+///
+/// let solved = DlxSolver::new().solve(&Board::new()).solution.unwrap();
+/// assert_eq!(1, DlxSolver::new().count_solutions(&solved, 2));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DlxSolver {
+    node_limit: Option<u64>,
+}
+
+impl DlxSolver {
+    pub fn new() -> DlxSolver {
+        DlxSolver::default()
+    }
+
+    /// Gives up once the search has chosen this many columns.
+    pub fn node_limit(mut self, limit: u64) -> DlxSolver {
+        self.node_limit = Some(limit);
+        self
+    }
+
+    /// Solves a copy of the board, leaving the original untouched.
+    pub fn solve(&mut self, board: &Board) -> SolveResult {
+        let start = Instant::now();
+        let mut search = match Search::new(board, self.node_limit) {
+            Ok(search) => search,
+            Err(error) => return SolveResult { solution: Err(error), stats: SolveStats::default() },
+        };
+
+        let solution = match search.next_solution() {
+            Ok(Some(solution)) => Ok(solution),
+            Ok(None) => Err(SudokuError::Unsolvable),
+            Err(error) => Err(error),
+        };
+        search.stats.elapsed = start.elapsed();
+
+        SolveResult { solution, stats: search.stats }
+    }
+
+    /// Solves the board in place. On failure the board is left as it was.
+    pub fn solve_in_place(&mut self, board: &mut Board) -> Result<SolveStats, SudokuError> {
+        let result = self.solve(board);
+        *board = result.solution?;
+
+        Ok(result.stats)
+    }
+
+    /// Counts the solutions of a board, stopping once `limit` have been found.
+    /// Invalid boards have no solutions. The node limit doesn't apply.
+    pub fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        self.solutions(board).take(limit).count()
+    }
+
+    /// Lazily enumerates every solution of a board. Invalid boards have no
+    /// solutions and the node limit doesn't apply.
+    pub fn solutions(&self, board: &Board) -> DlxSolutions {
+        DlxSolutions {
+            search: Search::new(board, None).ok(),
+        }
+    }
+}
+
+/// The solutions of a board in the order the exact cover search finds them.
+#[derive(Clone, Debug)]
+pub struct DlxSolutions {
+    search: Option<Search>,
+}
+
+impl Iterator for DlxSolutions {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        let solution = self.search.as_mut()?.next_solution().ok().flatten();
+        if solution.is_none() {
+            self.search = None;
+        }
+
+        solution
+    }
+}
+
+// the exact cover matrix as a torus of doubly linked nodes, nodes 1 up to the
+// number of columns are the column headers
+#[derive(Clone, Debug)]
+struct Matrix {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    // the number of nodes left in each column, indexed by header
+    size: Vec<usize>,
+    // the placement each node stands for
+    placement: Vec<((usize, usize), u8)>,
+}
+
+impl Matrix {
+    // only the placements that are legal in an empty cell get a row, and only
+    // the constraints the givens don't already cover get a column
+    fn new(candidates: &Candidates) -> Matrix {
+        let board = candidates.board();
+        let size = board.size();
+        let columns = 4 * size * size;
+
+        let mut covered = vec![false; columns];
+        for ((row, col), value) in board.cells() {
+            if value != 0 {
+                for column in constraints(board, row, col, value) {
+                    covered[column] = true;
+                }
+            }
+        }
+
+        let headers = columns + 1;
+        let mut matrix = Matrix {
+            left: (0..headers).collect(),
+            right: (0..headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            size: vec![0; headers],
+            placement: vec![((0, 0), 0); headers],
+        };
+
+        for header in (1..headers).filter(|&header| !covered[header - 1]) {
+            matrix.left[header] = matrix.left[ROOT];
+            matrix.right[header] = ROOT;
+            matrix.right[matrix.left[ROOT]] = header;
+            matrix.left[ROOT] = header;
+        }
+
+        for ((row, col), mask) in candidates.iter() {
+            for digit in digits(mask) {
+                matrix.add_row((row, col), digit, constraints(board, row, col, digit));
+            }
+        }
+
+        matrix
+    }
+
+    fn add_row(&mut self, position: (usize, usize), digit: u8, columns: [usize; 4]) {
+        let first = self.left.len();
+
+        for (i, &column) in columns.iter().enumerate() {
+            let header = column + 1;
+            let node = first + i;
+
+            self.left.push(if i == 0 { first + 3 } else { node - 1 });
+            self.right.push(if i == 3 { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            self.column.push(header);
+            self.placement.push((position, digit));
+
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                self.down[self.up[j]] = self.down[j];
+                self.up[self.down[j]] = self.up[j];
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                self.size[self.column[j]] += 1;
+                self.down[self.up[j]] = j;
+                self.up[self.down[j]] = j;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    // covers every other column of the node's row
+    fn select(&mut self, node: usize) {
+        let mut j = self.right[node];
+        while j != node {
+            self.cover(self.column[j]);
+            j = self.right[j];
+        }
+    }
+
+    fn deselect(&mut self, node: usize) {
+        let mut j = self.left[node];
+        while j != node {
+            self.uncover(self.column[j]);
+            j = self.left[j];
+        }
+    }
+
+    // the uncovered column with the fewest rows left, none once every
+    // constraint is covered
+    fn smallest_column(&self) -> Option<usize> {
+        let mut best = None;
+        let mut fewest = usize::MAX;
+
+        let mut header = self.right[ROOT];
+        while header != ROOT {
+            if self.size[header] < fewest {
+                best = Some(header);
+                fewest = self.size[header];
+            }
+            header = self.right[header];
+        }
+
+        best
+    }
+}
+
+// the cell, row, column and box constraints a placement covers
+fn constraints(board: &Board, row: usize, col: usize, digit: u8) -> [usize; 4] {
+    let size = board.size();
+    let digit = digit as usize - 1;
+
+    [
+        row * size + col,
+        size * size + row * size + digit,
+        2 * size * size + col * size + digit,
+        3 * size * size + board.box_index(row, col) * size + digit,
+    ]
+}
+
+// Algorithm X with an explicit stack so it can stop after each solution
+#[derive(Clone, Debug)]
+struct Search {
+    board: Board,
+    matrix: Matrix,
+    // the row chosen at each level
+    stack: Vec<usize>,
+    backtracking: bool,
+    node_limit: Option<u64>,
+    stats: SolveStats,
+}
+
+impl Search {
+    fn new(board: &Board, node_limit: Option<u64>) -> Result<Search, SudokuError> {
+        let candidates = Candidates::new(board)?;
+
+        Ok(Search {
+            board: board.clone(),
+            matrix: Matrix::new(&candidates),
+            stack: Vec::new(),
+            backtracking: false,
+            node_limit,
+            stats: SolveStats::default(),
+        })
+    }
+
+    fn next_solution(&mut self) -> Result<Option<Board>, SudokuError> {
+        loop {
+            if self.backtracking {
+                self.backtracking = false;
+
+                if !self.backtrack() {
+                    return Ok(None);
+                }
+            }
+
+            let nodes = self.stats.nodes;
+            if self.node_limit.is_some_and(|limit| nodes >= limit) {
+                return Err(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes });
+            }
+            self.stats.nodes += 1;
+
+            let header = match self.matrix.smallest_column() {
+                Some(header) => header,
+                None => {
+                    // resume from this solution on the next call
+                    self.backtracking = true;
+                    return Ok(Some(self.solution()));
+                }
+            };
+
+            self.matrix.cover(header);
+            let node = self.matrix.down[header];
+
+            if node == header {
+                self.matrix.uncover(header);
+                self.backtracking = true;
+            } else {
+                self.push(node);
+            }
+        }
+    }
+
+    fn push(&mut self, node: usize) {
+        self.matrix.select(node);
+        self.stack.push(node);
+
+        self.stats.placements += 1;
+        self.stats.max_depth = self.stats.max_depth.max(self.stack.len());
+        // covering a column leaves its own rows and size alone
+        if self.matrix.size[self.matrix.column[node]] > 1 {
+            self.stats.guesses += 1;
+        }
+    }
+
+    // undoes chosen rows until one of them has another row below it
+    fn backtrack(&mut self) -> bool {
+        while let Some(node) = self.stack.pop() {
+            self.matrix.deselect(node);
+            self.stats.backtracks += 1;
+
+            let header = self.matrix.column[node];
+            let next = self.matrix.down[node];
+            if next != header {
+                self.push(next);
+                return true;
+            }
+
+            self.matrix.uncover(header);
+        }
+
+        false
+    }
+
+    fn solution(&self) -> Board {
+        let mut solution = self.board.clone();
+        for &node in &self.stack {
+            let ((row, col), digit) = self.matrix.placement[node];
+            solution.set(row, col, digit);
+        }
+
+        solution
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::{puzzle, two_solutions};
+    use crate::game::{find_empty, is_valid_board};
+    use crate::solver::Solver;
+
+    #[test]
+    fn test_solve_matches_backtracker() {
+        let board = puzzle();
+        let expected = Solver::new().solve(&board).solution;
+
+        assert_eq!(expected, DlxSolver::new().solve(&board).solution);
+
+        let mut in_place = board.clone();
+        DlxSolver::new().solve_in_place(&mut in_place).unwrap();
+        assert_eq!(expected, Ok(in_place));
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        assert_eq!(1, DlxSolver::new().count_solutions(&puzzle(), 10));
+        assert_eq!(2, DlxSolver::new().count_solutions(&two_solutions(), 10));
+        assert_eq!(1, DlxSolver::new().count_solutions(&two_solutions(), 1));
+        assert_eq!(0, DlxSolver::new().count_solutions(&two_solutions(), 0));
+        assert_eq!(288, DlxSolver::new().count_solutions(&Board::empty(Geometry::new(2, 2).unwrap()), 1000));
+    }
+
+    #[test]
+    fn test_solutions() {
+        let mut found: Vec<Board> = DlxSolver::new().solutions(&two_solutions()).collect();
+        let mut expected: Vec<Board> = crate::solutions::solutions(&two_solutions()).collect();
+        found.sort_by_key(|board| board.to_rows());
+        expected.sort_by_key(|board| board.to_rows());
+
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn test_invalid_and_unsolvable() {
+        let mut board = puzzle();
+        board.set(0, 0, 2);
+        let duplicate = DlxSolver::new().solve(&board);
+
+        assert!(matches!(duplicate.solution, Err(SudokuError::DuplicateInUnit { .. })));
+        assert_eq!(SolveStats::default(), duplicate.stats);
+        assert_eq!(0, DlxSolver::new().count_solutions(&board, 10));
+        assert_eq!(None, DlxSolver::new().solutions(&board).next());
+
+        // row 8 leaves only a 9 for (8, 8) but column 8 already has one
+        let mut board = Board::new();
+        for col in 0..8 {
+            board.set(8, col, col as u8 + 1);
+        }
+        board.set(0, 8, 9);
+        assert_eq!(Err(SudokuError::Unsolvable), DlxSolver::new().solve(&board).solution);
+        assert_eq!(0, DlxSolver::new().count_solutions(&board, 10));
+    }
+
+    #[test]
+    fn test_node_limit() {
+        assert_eq!(
+            Err(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes: 5 }),
+            DlxSolver::new().node_limit(5).solve(&puzzle()).solution
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = DlxSolver::new().solve(&two_solutions()).stats;
+
+        assert_eq!(4, stats.max_depth);
+        assert_eq!(stats.nodes, stats.placements + 1);
+        assert_eq!(1, stats.guesses);
+    }
+
+    #[test]
+    fn test_sixteen_by_sixteen() {
+        let geometry = Geometry::new(4, 4).unwrap();
+        let solution = DlxSolver::new().solve(&Board::empty(geometry)).solution.unwrap();
+
+        assert!(is_valid_board(&solution) && find_empty(&solution).is_none());
+
+        // blank a few cells out of a full grid, it still has a solution
+        let mut board = solution.clone();
+        for i in 0..16 {
+            board.set(i, (i * 7) % 16, 0);
+        }
+        assert_eq!(Ok(solution), DlxSolver::new().solve(&board).solution);
+    }
+}
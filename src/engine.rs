@@ -0,0 +1,489 @@
+use std::convert::TryInto;
+
+use crate::board::{Board, Geometry};
+use crate::candidates::{digit_bit, Candidates};
+use crate::error::SudokuError;
+use crate::game::find_empty;
+use crate::solver::SolveStats;
+
+// the first bytes of every snapshot, the last one is the format version
+const MAGIC: &[u8; 4] = b"SDK\x01";
+
+/// What one step of a `SearchEngine` did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// A digit went into a cell, replacing the one tried before it if any.
+    Placed { row: usize, col: usize, digit: u8 },
+    /// The next cell to fill has no legal digit.
+    DeadEnd { row: usize, col: usize },
+    /// Every digit has been tried in a cell and it was emptied again.
+    Backtracked { row: usize, col: usize },
+    /// The board is full, stepping again looks for the next solution.
+    Solved(Board),
+    /// Every solution has been found.
+    Exhausted,
+}
+
+// a filled cell, the digit in it and the digits still to try there
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Frame {
+    cell: usize,
+    digit: u8,
+    remaining: u32,
+    guessing: bool,
+}
+
+/// A backtracking search kept on an explicit stack, so it can be stepped
+/// one decision at a time, inspected between steps and saved to bytes to be
+/// picked up again later. It fills the first empty cell with the smallest
+/// digit first, the same as `Solver` with its default options.
+///
+/// ```
+/// use sudoku_solver::{Board, SearchEngine};
+///
+/// let mut engine = SearchEngine::new(&Board::new()).unwrap();
+/// for _ in 0..10 {
+///     engine.step();
+/// }
+///
+/// let mut resumed = SearchEngine::from_bytes(&engine.to_bytes()).unwrap();
+/// assert_eq!(engine.next_solution(), resumed.next_solution());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchEngine {
+    givens: Board,
+    candidates: Candidates,
+    stack: Vec<Frame>,
+    // the last step found a solution or a dead end
+    backtracking: bool,
+    finished: bool,
+    stats: SolveStats,
+}
+
+impl SearchEngine {
+    pub fn new(board: &Board) -> Result<SearchEngine, SudokuError> {
+        Ok(SearchEngine {
+            givens: board.clone(),
+            candidates: Candidates::new(board)?,
+            stack: Vec::new(),
+            backtracking: false,
+            finished: false,
+            stats: SolveStats::default(),
+        })
+    }
+
+    /// The board as the search has it right now.
+    pub fn board(&self) -> &Board {
+        self.candidates.board()
+    }
+
+    /// How many cells the search has filled.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// What the search has done so far. The engine doesn't keep time, so
+    /// `elapsed` is always zero.
+    pub fn stats(&self) -> &SolveStats {
+        &self.stats
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Makes one decision, placing a digit or taking one out.
+    pub fn step(&mut self) -> Step {
+        if self.finished {
+            return Step::Exhausted;
+        }
+
+        if self.backtracking {
+            return self.backtrack();
+        }
+
+        self.stats.nodes += 1;
+
+        let size = self.board().size();
+        let start = self.stack.last().map_or(0, |frame| frame.cell + 1);
+        let cell = match (start..size * size).find(|&cell| self.board()[position(size, cell)] == 0) {
+            Some(cell) => cell,
+            None => {
+                // resume from this solution on the next step
+                self.backtracking = true;
+                return Step::Solved(self.board().clone());
+            }
+        };
+
+        let (row, col) = position(size, cell);
+        let mask = self.candidates.get(row, col);
+        if mask == 0 {
+            self.backtracking = true;
+            return Step::DeadEnd { row, col };
+        }
+
+        self.stack.push(Frame {
+            cell,
+            digit: 0,
+            remaining: mask,
+            guessing: mask.count_ones() > 1,
+        });
+        self.stats.max_depth = self.stats.max_depth.max(self.stack.len());
+
+        self.place_next()
+    }
+
+    /// Steps until the next solution, none once they have all been found.
+    pub fn next_solution(&mut self) -> Option<Board> {
+        loop {
+            match self.step() {
+                Step::Solved(solution) => return Some(solution),
+                Step::Exhausted => return None,
+                _ => {}
+            }
+        }
+    }
+
+    // takes the digit out of the newest cell and tries its next one
+    fn backtrack(&mut self) -> Step {
+        let size = self.board().size();
+        let frame = match self.stack.last() {
+            Some(frame) => frame,
+            None => {
+                self.finished = true;
+                return Step::Exhausted;
+            }
+        };
+
+        let (row, col) = position(size, frame.cell);
+        let remaining = frame.remaining;
+        self.candidates.remove(row, col);
+        self.stats.backtracks += 1;
+
+        if remaining == 0 {
+            self.stack.pop();
+            return Step::Backtracked { row, col };
+        }
+
+        self.backtracking = false;
+        self.place_next()
+    }
+
+    fn place_next(&mut self) -> Step {
+        let size = self.board().size();
+        let frame = self.stack.last_mut().unwrap();
+        let (row, col) = position(size, frame.cell);
+
+        let digit = frame.remaining.trailing_zeros() as u8 + 1;
+        frame.remaining &= !digit_bit(digit);
+        frame.digit = digit;
+
+        self.stats.placements += 1;
+        if frame.guessing {
+            self.stats.guesses += 1;
+        }
+        self.candidates.fill(row, col, digit);
+
+        Step::Placed { row, col, digit }
+    }
+
+    /// Saves the search so `from_bytes` can carry on from exactly here.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let geometry = self.givens.geometry();
+        let mut bytes = MAGIC.to_vec();
+
+        bytes.push(geometry.box_rows() as u8);
+        bytes.push(geometry.box_cols() as u8);
+        bytes.extend(self.givens.cells().map(|(_, value)| value));
+        bytes.push(self.backtracking as u8);
+        bytes.push(self.finished as u8);
+
+        bytes.extend(&(self.stack.len() as u32).to_le_bytes());
+        for frame in &self.stack {
+            bytes.extend(&(frame.cell as u32).to_le_bytes());
+            bytes.push(frame.digit);
+            bytes.extend(&frame.remaining.to_le_bytes());
+            bytes.push(frame.guessing as u8);
+        }
+
+        let stats = &self.stats;
+        for count in &[stats.nodes, stats.placements, stats.propagated, stats.backtracks, stats.max_depth as u64, stats.guesses] {
+            bytes.extend(&count.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    /// Restores a search saved by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<SearchEngine, SudokuError> {
+        let mut reader = Reader { bytes };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(snapshot("not a search engine snapshot"));
+        }
+
+        let (box_rows, box_cols) = (reader.byte()? as usize, reader.byte()? as usize);
+        let geometry = Geometry::new(box_rows, box_cols).map_err(|_| snapshot("unsupported box size"))?;
+        let size = geometry.size();
+
+        // the board can't work out a 6 x 6 geometry from its cells alone
+        let mut givens = Board::empty(geometry);
+        for (cell, &value) in reader.take(size * size)?.iter().enumerate() {
+            givens[position(size, cell)] = value;
+        }
+        let mut engine = SearchEngine::new(&givens).map_err(|_| snapshot("the givens break the rules"))?;
+        engine.backtracking = reader.byte()? != 0;
+        engine.finished = reader.byte()? != 0;
+
+        let frames = reader.u32()? as usize;
+        for _ in 0..frames {
+            let frame = Frame {
+                cell: reader.u32()? as usize,
+                digit: reader.byte()?,
+                remaining: reader.u32()?,
+                guessing: reader.byte()? != 0,
+            };
+
+            // the engine always fills the first empty cell after the last one
+            let start = engine.stack.last().map_or(0, |frame| frame.cell + 1);
+            let next = (start..size * size).find(|&cell| engine.board()[position(size, cell)] == 0);
+            if next != Some(frame.cell) {
+                return Err(snapshot("cells out of order"));
+            }
+
+            // the digits left to try are the cell's other candidates
+            let (row, col) = position(size, frame.cell);
+            let mask = engine.candidates.get(row, col);
+            let in_range = (1..=size).contains(&(frame.digit as usize));
+            if !in_range || frame.remaining & !mask != 0 || frame.remaining & digit_bit(frame.digit) != 0 {
+                return Err(snapshot("digits that aren't candidates"));
+            }
+
+            // digits are tried smallest first, so only larger ones are left
+            if (frame.remaining.trailing_zeros() as usize) < frame.digit as usize {
+                return Err(snapshot("digits tried out of order"));
+            }
+
+            engine.candidates.place(row, col, frame.digit).map_err(|_| snapshot("illegal placement"))?;
+            engine.stack.push(frame);
+        }
+
+        // the search only finishes once every cell it filled is empty again,
+        // and only backtracks from nothing when there was nothing to fill
+        if engine.finished && !engine.stack.is_empty() {
+            return Err(snapshot("finished with cells still filled"));
+        }
+        if engine.backtracking && !engine.finished && engine.stack.is_empty() {
+            let first = find_empty(engine.board());
+            if first.is_some_and(|(row, col)| engine.candidates.get(row, col) != 0) {
+                return Err(snapshot("backtracking without a cell to take back"));
+            }
+        }
+
+        let stats = &mut engine.stats;
+        stats.nodes = reader.u64()?;
+        stats.placements = reader.u64()?;
+        stats.propagated = reader.u64()?;
+        stats.backtracks = reader.u64()?;
+        stats.max_depth = reader.u64()? as usize;
+        stats.guesses = reader.u64()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(snapshot("trailing bytes"));
+        }
+
+        Ok(engine)
+    }
+}
+
+fn position(size: usize, cell: usize) -> (usize, usize) {
+    (cell / size, cell % size)
+}
+
+fn snapshot(reason: &str) -> SudokuError {
+    SudokuError::InvalidSnapshot { reason: reason.to_string() }
+}
+
+struct Reader<'b> {
+    bytes: &'b [u8],
+}
+
+impl<'b> Reader<'b> {
+    fn take(&mut self, count: usize) -> Result<&'b [u8], SudokuError> {
+        if self.bytes.len() < count {
+            return Err(snapshot("too short"));
+        }
+
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, SudokuError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SudokuError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SudokuError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::{two_solutions, unsolvable};
+    use crate::solver::Solver;
+
+    #[test]
+    fn test_steps() {
+        let mut engine = SearchEngine::new(&two_solutions()).unwrap();
+        let mut steps = Vec::new();
+
+        loop {
+            let step = engine.step();
+            if step == Step::Exhausted {
+                break;
+            }
+            steps.push(step);
+        }
+
+        let first = Solver::new().solve(&two_solutions()).solution.unwrap();
+        assert_eq!(Step::Placed { row: 0, col: 2, digit: 5 }, steps[0]);
+        assert_eq!(Step::Solved(first), steps[4]);
+        assert_eq!(Step::Placed { row: 0, col: 2, digit: 8 }, steps[8]);
+        assert_eq!(Step::Backtracked { row: 0, col: 2 }, steps[steps.len() - 1]);
+        assert_eq!(2, steps.iter().filter(|step| matches!(step, Step::Solved(_))).count());
+        assert!(engine.is_finished());
+        assert_eq!(0, engine.depth());
+        assert_eq!(&two_solutions(), engine.board());
+        assert_eq!(Step::Exhausted, engine.step());
+    }
+
+    #[test]
+    fn test_dead_end() {
+        // the 4 has to go in (0, 2), leaving nothing for (0, 3)
+        let board = unsolvable();
+        let mut engine = SearchEngine::new(&board).unwrap();
+
+        assert_eq!(Step::Placed { row: 0, col: 2, digit: 4 }, engine.step());
+        assert_eq!(Step::DeadEnd { row: 0, col: 3 }, engine.step());
+        assert_eq!(1, engine.depth());
+        assert_eq!(Step::Backtracked { row: 0, col: 2 }, engine.step());
+        assert_eq!(Step::Exhausted, engine.step());
+        assert_eq!(None, engine.next_solution());
+    }
+
+    #[test]
+    fn test_matches_solver_stats() {
+        let mut engine = SearchEngine::new(&two_solutions()).unwrap();
+        engine.next_solution().unwrap();
+        let stats = Solver::new().solve(&two_solutions()).stats;
+
+        assert_eq!(
+            (stats.nodes, stats.placements, stats.backtracks, stats.max_depth, stats.guesses),
+            (engine.stats().nodes, engine.stats().placements, engine.stats().backtracks, engine.stats().max_depth, engine.stats().guesses)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let board = Board::empty(Geometry::new(3, 2).unwrap());
+        let mut engine = SearchEngine::new(&board).unwrap();
+
+        for steps in 0..200 {
+            let snapshot = engine.to_bytes();
+            let resumed = SearchEngine::from_bytes(&snapshot).unwrap();
+            assert_eq!(engine, resumed, "after {} steps", steps);
+            assert_eq!(snapshot, resumed.to_bytes());
+
+            engine.step();
+        }
+
+        let mut resumed = SearchEngine::from_bytes(&engine.to_bytes()).unwrap();
+        let rest: Vec<Board> = std::iter::from_fn(|| engine.next_solution()).take(20).collect();
+        assert_eq!(rest, std::iter::from_fn(|| resumed.next_solution()).take(20).collect::<Vec<Board>>());
+    }
+
+    #[test]
+    fn test_bad_snapshots() {
+        let mut engine = SearchEngine::new(&two_solutions()).unwrap();
+        engine.step();
+        engine.step();
+        let bytes = engine.to_bytes();
+        let bad = |reason: &str| Err(snapshot(reason));
+
+        assert_eq!(bad("not a search engine snapshot"), SearchEngine::from_bytes(b"nope"));
+        assert_eq!(bad("too short"), SearchEngine::from_bytes(&bytes[..bytes.len() - 1]));
+        assert_eq!(bad("trailing bytes"), SearchEngine::from_bytes(&[&bytes[..], &[0]].concat()));
+
+        let mut unsupported = bytes.clone();
+        unsupported[4] = 0;
+        assert_eq!(bad("unsupported box size"), SearchEngine::from_bytes(&unsupported));
+
+        // the first frame's digit comes after the counts and the cell index
+        let mut illegal = bytes.clone();
+        illegal[4 + 2 + 81 + 2 + 4 + 4] = 1;
+        assert_eq!(bad("illegal placement"), SearchEngine::from_bytes(&illegal));
+
+        // the remaining digits follow it, (0, 2) can only hold a 5 or an 8
+        let remaining = 4 + 2 + 81 + 2 + 4 + 4 + 1;
+        let mut not_candidate = bytes.clone();
+        not_candidate[remaining] |= 1;
+        assert_eq!(bad("digits that aren't candidates"), SearchEngine::from_bytes(&not_candidate));
+
+        let mut placed_again = bytes.clone();
+        placed_again[remaining] |= digit_bit(5) as u8;
+        assert_eq!(bad("digits that aren't candidates"), SearchEngine::from_bytes(&placed_again));
+
+        // an 8 with the 5 still to try, the engine would have tried the 5 first
+        let mut out_of_order = bytes.clone();
+        out_of_order[remaining - 1] = 8;
+        out_of_order[remaining] = digit_bit(5) as u8;
+        assert_eq!(bad("digits tried out of order"), SearchEngine::from_bytes(&out_of_order));
+    }
+
+    #[test]
+    fn test_snapshot_flags() {
+        let mut engine = SearchEngine::new(&two_solutions()).unwrap();
+        let fresh = engine.to_bytes();
+        engine.step();
+        let stepped = engine.to_bytes();
+        let (backtracking, finished) = (4 + 2 + 81, 4 + 2 + 81 + 1);
+
+        let mut finished_early = stepped.clone();
+        finished_early[finished] = 1;
+        assert_eq!(Err(snapshot("finished with cells still filled")), SearchEngine::from_bytes(&finished_early));
+
+        let mut backtracking_from_nothing = fresh.clone();
+        backtracking_from_nothing[backtracking] = 1;
+        assert_eq!(
+            Err(snapshot("backtracking without a cell to take back")),
+            SearchEngine::from_bytes(&backtracking_from_nothing)
+        );
+
+        // a full board is solved before anything is filled in
+        let mut full = SearchEngine::new(&Solver::new().solve(&two_solutions()).solution.unwrap()).unwrap();
+        assert!(matches!(full.step(), Step::Solved(_)));
+        assert_eq!(Ok(full.clone()), SearchEngine::from_bytes(&full.to_bytes()));
+        assert_eq!(Step::Exhausted, full.step());
+        assert_eq!(Ok(full.clone()), SearchEngine::from_bytes(&full.to_bytes()));
+    }
+
+    #[test]
+    fn test_snapshot_skipping_cells() {
+        let mut engine = SearchEngine::new(&Board::new()).unwrap();
+        engine.step();
+        let mut bytes = engine.to_bytes();
+
+        // move the only frame from (0, 0) to (8, 8), leaving (0, 0) empty
+        let cell = 4 + 2 + 81 + 2 + 4;
+        bytes[cell..cell + 4].copy_from_slice(&80u32.to_le_bytes());
+        assert_eq!(Err(snapshot("cells out of order")), SearchEngine::from_bytes(&bytes));
+    }
+}
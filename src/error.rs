@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::board::Unit;
+use crate::solver::Budget;
+
+/// Everything that can go wrong when building, validating or solving a board.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SudokuError {
+    WrongDimensions { rows: usize, cols: usize },
+    UnsupportedGeometry { box_rows: usize, box_cols: usize },
+    ValueOutOfRange { row: usize, col: usize, value: u8 },
+    DuplicateInUnit { unit: Unit, value: u8, cells: Vec<(usize, usize)> },
+    IllegalMove { row: usize, col: usize, value: u8 },
+    Unsolvable,
+    BudgetExhausted { budget: Budget, nodes: u64 },
+    Cancelled { nodes: u64 },
+    InvalidModel { reason: String },
+    InvalidSnapshot { reason: String },
+    UnknownBackend { name: String },
+    BackendMismatch { first: &'static str, second: &'static str, reason: String },
+}
+
+impl fmt::Display for SudokuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SudokuError::WrongDimensions { rows, cols } => {
+                write!(f, "a {} x {} board is not a supported size", rows, cols)
+            }
+            SudokuError::UnsupportedGeometry { box_rows, box_cols } => {
+                write!(f, "boxes of {} x {} are not supported", box_rows, box_cols)
+            }
+            SudokuError::ValueOutOfRange { row, col, value } => {
+                write!(f, "value {} at ({}, {}) is out of range", value, row, col)
+            }
+            SudokuError::DuplicateInUnit { unit, value, cells } => {
+                write!(f, "{} appears {} times in {}", value, cells.len(), unit)
+            }
+            SudokuError::IllegalMove { row, col, value } => {
+                write!(f, "{} cannot be placed at ({}, {})", value, row, col)
+            }
+            SudokuError::Unsolvable => write!(f, "puzzle is unsolvable"),
+            SudokuError::BudgetExhausted { budget, nodes } => {
+                let budget = match budget {
+                    Budget::Nodes => "node",
+                    Budget::Time => "time",
+                };
+                write!(f, "{} budget ran out after visiting {} nodes", budget, nodes)
+            }
+            SudokuError::Cancelled { nodes } => {
+                write!(f, "cancelled after visiting {} nodes", nodes)
+            }
+            SudokuError::InvalidModel { reason } => write!(f, "invalid model: {}", reason),
+            SudokuError::InvalidSnapshot { reason } => write!(f, "invalid snapshot: {}", reason),
+            SudokuError::UnknownBackend { name } => write!(f, "unknown backend: {}", name),
+            SudokuError::BackendMismatch { first, second, reason } => {
+                write!(f, "{} and {} disagree: {}", first, second, reason)
+            }
+        }
+    }
+}
+
+impl Error for SudokuError {}
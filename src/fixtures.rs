@@ -0,0 +1,74 @@
+use crate::board::Board;
+
+/// A solved board with (0, 2), (0, 5), (1, 2) and (1, 5) emptied, which can
+/// take 5 and 8 either way round.
+pub(crate) fn two_solutions() -> Board {
+    Board::from_rows(&[
+        vec!(1, 3, 0, 2, 7, 0, 9, 6, 4,),
+        vec!(7, 9, 0, 6, 4, 0, 2, 3, 1,),
+        vec!(6, 4, 2, 3, 9, 1, 8, 5, 7,),
+        vec!(5, 2, 7, 4, 6, 3, 1, 9, 8,),
+        vec!(9, 1, 6, 5, 8, 2, 4, 7, 3,),
+        vec!(4, 8, 3, 7, 1, 9, 5, 2, 6,),
+        vec!(2, 6, 1, 9, 3, 4, 7, 8, 5,),
+        vec!(3, 5, 4, 8, 2, 7, 6, 1, 9,),
+        vec!(8, 7, 9, 1, 5, 6, 3, 4, 2,),
+    ]).unwrap()
+}
+
+/// A puzzle with a single solution that takes some guessing.
+pub(crate) fn puzzle() -> Board {
+    Board::from_rows(&[
+        vec!(0, 0, 0, 0, 0, 2, 7, 3, 4,),
+        vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
+        vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
+        vec!(0, 0, 0, 0, 0, 1, 0, 0, 0,),
+        vec!(4, 0, 6, 0, 2, 0, 0, 1, 3,),
+        vec!(0, 0, 8, 0, 0, 0, 9, 4, 0,),
+        vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
+        vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
+        vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
+    ]).unwrap()
+}
+
+/// A puzzle whose only solution is `solution()`, mostly found without
+/// guessing.
+pub(crate) fn easy_puzzle() -> Board {
+    Board::from_rows(&[
+        vec!(0, 0, 5, 0, 7, 0, 9, 0, 4,),
+        vec!(0, 9, 0, 0, 4, 0, 2, 3, 1,),
+        vec!(6, 0, 2, 0, 9, 1, 0, 0, 0,),
+        vec!(5, 0, 0, 4, 0, 3, 0, 0, 8,),
+        vec!(0, 1, 6, 5, 0, 2, 0, 0, 0,),
+        vec!(0, 8, 0, 0, 1, 0, 5, 2, 6,),
+        vec!(2, 6, 0, 0, 0, 0, 0, 8, 5,),
+        vec!(3, 0, 0, 8, 0, 7, 0, 1, 0,),
+        vec!(8, 0, 9, 0, 0, 0, 0, 4, 2,),
+    ]).unwrap()
+}
+
+/// The solution of `easy_puzzle()`.
+pub(crate) fn solution() -> Board {
+    Board::from_rows(&[
+        vec!(1, 3, 5, 2, 7, 8, 9, 6, 4,),
+        vec!(7, 9, 8, 6, 4, 5, 2, 3, 1,),
+        vec!(6, 4, 2, 3, 9, 1, 8, 5, 7,),
+        vec!(5, 2, 7, 4, 6, 3, 1, 9, 8,),
+        vec!(9, 1, 6, 5, 8, 2, 4, 7, 3,),
+        vec!(4, 8, 3, 7, 1, 9, 5, 2, 6,),
+        vec!(2, 6, 1, 9, 3, 4, 7, 8, 5,),
+        vec!(3, 5, 4, 8, 2, 7, 6, 1, 9,),
+        vec!(8, 7, 9, 1, 5, 6, 3, 4, 2,),
+    ]).unwrap()
+}
+
+/// A 4 x 4 board with no solution, row 0 needs its 3 in (0, 2) or (0, 3)
+/// but box 1 already has one at (1, 2).
+pub(crate) fn unsolvable() -> Board {
+    Board::from_rows(&[
+        vec!(1, 2, 0, 0,),
+        vec!(0, 0, 3, 0,),
+        vec!(0, 0, 0, 0,),
+        vec!(0, 0, 0, 0,),
+    ]).unwrap()
+}
@@ -1,187 +1,83 @@
-use std::collections::HashSet;
-
-fn find_empty(board: &[Vec<u8>]) -> Option<(u8, u8)> {
-    for (row, _) in board.iter().enumerate() {
-        for col in 0..board[row].len() {
-            if board[row][col] == 0 {
-                return Some((row as u8, col as u8,));
-            }
-        }
-    }
-
-    None
+use crate::board::{Board, Unit};
+use crate::constraints::Rules;
+use crate::error::SudokuError;
+use crate::solver::Solver;
+
+/// The first empty cell in row-major order.
+pub fn find_empty(board: &Board) -> Option<(usize, usize)> {
+    board.cells().find(|&(_, value)| value == 0).map(|(position, _)| position)
 }
 
-fn get_solution(solved: bool, board: &mut Vec<Vec<u8>>) -> (bool, &Vec<Vec<u8>>) {
-    let find = find_empty(&board);
-    
-    if find.is_none() {
-        return (true, board);
-    }
-
-    let row = find.unwrap().0 as usize;
-    let col = find.unwrap().1 as usize;
-
-    for i in 1..10 {
-        if is_valid_move(board, &(row, col,), i) {
-            board[row][col] = i;
-
-            if get_solution(solved, board).0 {
-                return (true, board,);
-            }
-
-            board[row][col] = 0;
-        }
-    }
+/// Solves the board in place with the default `Solver`.
+pub fn solve_puzzle(board: &mut Board) -> Result<&Board, SudokuError> {
+    Solver::new().solve_in_place(board)?;
 
-    (false, board)
+    Ok(board)
 }
 
-pub fn print_board(board: &[Vec<u8>]) {
-    if !is_valid_board(board) {
-        println!("Board is invalid");
-        return;
-    }
-
-    for row in 0..board.len() {
-        if row % 3 == 0 && row != 0 {
-            println!("- - - - - - - - - - - - - -");
-        }
-        for col in 0..board[0].len() {
-            if col % 3 == 0 {
-                print!("| {} ", board[row][col]);
-            } else if col == 8 {
-                println!("{} |", board[row][col]);
-            } else {
-                print!("{} ", board[row][col]);
-            }
-        }
-    }
+/// Counts the solutions of a board, stopping as soon as `limit` have been
+/// found. Invalid boards have no solutions.
+pub fn count_solutions(board: &Board, limit: usize) -> usize {
+    Solver::new().count_solutions(board, limit)
 }
 
-pub fn solve_puzzle(board: &mut Vec<Vec<u8>>, print_solution: bool) -> Option<&Vec<Vec<u8>>> {
-    if !is_valid_board(board) {
-        println!("Board is invalid");
-        return None;
-    }
-
-    let solution = get_solution(false, board);
-    let is_solved = solution.0;
-    let solved_board = solution.1;
-
-    if !is_solved {
-        println!("Puzzle is unsolvable");
-        None
-    } else if is_solved {
-        for pos in solved_board { 
-            if pos.contains(&(0)) {
-                println!("Puzzle is unsolvable");
-                return None
-            }
-        }
-
-        if print_solution {
-            print_board(&solved_board);
-        }
-
-        Some(solved_board)
-    } else {
-        None
-    }
+/// Whether a board has exactly one solution.
+pub fn has_unique_solution(board: &Board) -> bool {
+    count_solutions(board, 2) == 1
 }
 
-fn is_valid_move(board: &[Vec<u8>], position: &(usize, usize), num: u8) -> bool {
-    // checks if the number is used in a row or column
-    for i in 0..board.len() {
-        if board[position.0][i] == num ||
-                board[i][position.1] == num {
-            return false;
-        }
-    }
-
-    // checks if number is used in a box
-    let row = position.0 - position.0 % 3;
-    let col = position.1 - position.1 % 3;
-
-    for i in 0..3 {
-        for j in 0..3 {
-            if board[i+row][j+col] == num {
-                return false;
-            }
-        }
-    }
+/// Whether `num` can go at `position` without repeating a value in its row,
+/// column or box.
+pub fn is_valid_move(board: &Board, position: &(usize, usize), num: u8) -> bool {
+    Rules::classic(board.geometry()).is_valid_move(board, position, num)
+}
 
-    true
+/// Whether every value is in range and no row, column or box repeats one.
+pub fn is_valid_board(board: &Board) -> bool {
+    validate_board(board).is_ok()
 }
 
-fn is_valid_board(board: &[Vec<u8>]) -> bool {
-    let rows = board.len();
-    let cols = board[0].len();
-    
-    // make sure the board is 9 x 9
-    if rows != 9 || cols != 9 {
-        return false;
-    }
+/// Same checks as `is_valid_board` but reports the first problem found.
+pub fn validate_board(board: &Board) -> Result<(), SudokuError> {
+    Rules::classic(board.geometry()).validate(board)
+}
 
-    for row in board.iter().take(rows){
-        // make sure all the values in a row other than 0 are unique
-        //let filtered: Vec<u8> = board[i].iter().cloned().filter(|&x| x!=0).collect();
-        
-        if !is_unique_vector(&row) {
-            return false;
-        }
+/// A single problem that makes a board invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    OutOfRange { row: usize, col: usize, value: u8 },
+    Duplicate { unit: Unit, value: u8, cells: Vec<(usize, usize)> },
+    /// A digit one of the extra constraints in a set of `Rules` doesn't allow.
+    Constraint { row: usize, col: usize, value: u8 },
+}
 
-        // make sure all numbers are between 0 and 9
-        for col in row.iter().take(cols) {
-            if col > &9 {
-                return false;
+impl From<Conflict> for SudokuError {
+    fn from(conflict: Conflict) -> SudokuError {
+        match conflict {
+            Conflict::OutOfRange { row, col, value } => {
+                SudokuError::ValueOutOfRange { row, col, value }
             }
-        }
-    }
-
-    // check for the same number, other than 0, in a column
-    let mut transposed_board: Vec<Vec<u8>> = vec![Vec::with_capacity(rows); cols];
-    for row in board.iter().take(rows) {
-        for j in 0..cols {
-            transposed_board[j].push(row[j]);
-        }
-    }
-
-    for row in transposed_board {
-        if !is_unique_vector(&row) {
-           return false;
-        }
-    }
-
-    // makes sure all the numbers, other than 0, in a box are unique
-    for row in (0..7).step_by(3) {
-        for col in (0..7).step_by(3) {
-            let mut board_box: Vec<u8> = Vec::with_capacity(rows);
-            for i in 0..3 {
-                for j in 0..3 {
-                    board_box.push(board[row + i][col + j]);
-                }
+            Conflict::Duplicate { unit, value, cells } => {
+                SudokuError::DuplicateInUnit { unit, value, cells }
             }
-        
-            if !is_unique_vector(&board_box) {
-                return false;
+            Conflict::Constraint { row, col, value } => {
+                SudokuError::IllegalMove { row, col, value }
             }
         }
     }
-
-    true
 }
 
-fn is_unique_vector(vector: &[u8]) -> bool {
-    let filtered: Vec<u8> = vector.iter().cloned().filter(|&x| x!=0).collect();
-    let mut unique = HashSet::new();
-
-    filtered.into_iter().all(move |x| unique.insert(x))
+/// Every out of range value followed by every repeated value in each row,
+/// column and box, so an editor can highlight all of the offending cells.
+pub fn find_conflicts(board: &Board) -> Vec<Conflict> {
+    Rules::classic(board.geometry()).find_conflicts(board)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures;
 
     macro_rules! test_find_empty {
         ($($name:ident: $value:expr,)*) => {
@@ -189,7 +85,8 @@ mod test {
                 #[test]
                 fn $name() {
                     let (input, expected) = $value;
-                    assert_eq!(expected, find_empty(&input).unwrap());
+                    let board = Board::from_rows(&input).unwrap();
+                    assert_eq!(expected, find_empty(&board).unwrap());
                 }
             )*
         }
@@ -240,7 +137,8 @@ mod test {
                 #[test]
                 fn $name() {
                     let (input, expected) = $value;
-                    assert_eq!(expected, is_valid_board(&input));
+                    let valid = Board::from_rows(&input).map_or(false, |board| is_valid_board(&board));
+                    assert_eq!(expected, valid);
                 }
             )*
         }
@@ -446,13 +344,97 @@ mod test {
         ), false),
     }
 
+    #[test]
+    fn test_validate_board_duplicate() {
+        let board = Board::from_rows(&[
+            vec!(2, 0, 0, 0, 0, 2, 7, 3, 4,),
+            vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
+            vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 1, 0, 0, 0,),
+            vec!(4, 0, 6, 0, 2, 0, 0, 1, 3,),
+            vec!(0, 0, 8, 0, 0, 0, 9, 4, 0,),
+            vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
+            vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
+        ]).unwrap();
+
+        let expected = SudokuError::DuplicateInUnit {
+            unit: Unit::Row(0),
+            value: 2,
+            cells: vec!((0, 0), (0, 5)),
+        };
+        assert_eq!(Err(expected), validate_board(&board));
+    }
+
+    #[test]
+    fn test_validate_board_out_of_range() {
+        let mut board = Board::new();
+        board.set(8, 8, 10);
+
+        let expected = SudokuError::ValueOutOfRange { row: 8, col: 8, value: 10 };
+        assert_eq!(Err(expected.clone()), validate_board(&board));
+        assert_eq!(Err(expected), solve_puzzle(&mut board));
+    }
+
+    #[test]
+    fn test_find_conflicts() {
+        let board = Board::from_rows(&[
+            vec!(2, 0, 0, 0, 0, 2, 7, 3, 4,),
+            vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
+            vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 1, 0, 0, 0,),
+            vec!(4, 0, 6, 0, 2, 0, 0, 1, 3,),
+            vec!(0, 0, 8, 0, 0, 0, 9, 4, 0,),
+            vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
+            vec!(0, 8, 0, 0, 3, 0, 5, 2, 12,),
+        ]).unwrap();
+
+        assert_eq!(
+            vec!(
+                Conflict::OutOfRange { row: 8, col: 8, value: 12 },
+                Conflict::Duplicate { unit: Unit::Row(0), value: 2, cells: vec!((0, 0), (0, 5)) },
+                Conflict::Duplicate { unit: Unit::Box(8), value: 2, cells: vec!((7, 8), (8, 7)) },
+            ),
+            find_conflicts(&board)
+        );
+    }
+
+    #[test]
+    fn test_find_conflicts_repeated_values() {
+        let board = Board::from_rows(&[
+            vec!(1, 1, 1, 5, 0, 5, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 0, 0, 0, 0,),
+        ]).unwrap();
+
+        assert_eq!(
+            vec!(
+                Conflict::Duplicate { unit: Unit::Row(0), value: 1, cells: vec!((0, 0), (0, 1), (0, 2)) },
+                Conflict::Duplicate { unit: Unit::Row(0), value: 5, cells: vec!((0, 3), (0, 5)) },
+                Conflict::Duplicate { unit: Unit::Box(0), value: 1, cells: vec!((0, 0), (0, 1), (0, 2)) },
+                Conflict::Duplicate { unit: Unit::Box(1), value: 5, cells: vec!((0, 3), (0, 5)) },
+            ),
+            find_conflicts(&board)
+        );
+        assert!(find_conflicts(&Board::new()).is_empty());
+    }
+
     macro_rules! test_solve_puzzle {
         ($($name:ident: $value:expr,)*) => {
             $(
                 #[test]
                 fn $name() {
-                    let (mut input, expected) = $value;
-                    assert_eq!(&expected, solve_puzzle(&mut input, false).unwrap());
+                    let (input, expected) = $value;
+                    let mut board = Board::from_rows(&input).unwrap();
+                    let expected = Board::from_rows(&expected).unwrap();
+                    assert_eq!(&expected, solve_puzzle(&mut board).unwrap());
                 }
             )*
         }
@@ -471,15 +453,15 @@ mod test {
             vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
         ),
         vec!(
-            vec!(8 as u8, 1 as u8, 5 as u8, 6 as u8, 9 as u8, 2 as u8, 7 as u8, 3 as u8, 4 as u8,),
-            vec!(7 as u8, 3 as u8, 2 as u8, 4 as u8, 1 as u8, 5 as u8, 6 as u8, 9 as u8, 8 as u8,),
-            vec!(6 as u8, 4 as u8, 9 as u8, 3 as u8, 7 as u8, 8 as u8, 1 as u8, 2 as u8, 5 as u8,),
-            vec!(3 as u8, 9 as u8, 7 as u8, 8 as u8, 4 as u8, 1 as u8, 2 as u8, 5 as u8, 6 as u8,),
-            vec!(4 as u8, 5 as u8, 6 as u8, 7 as u8, 2 as u8, 9 as u8, 8 as u8, 1 as u8, 3 as u8,),
-            vec!(1 as u8, 2 as u8, 8 as u8, 5 as u8, 6 as u8, 3 as u8, 9 as u8, 4 as u8, 7 as u8,),
-            vec!(9 as u8, 6 as u8, 3 as u8, 2 as u8, 5 as u8, 7 as u8, 4 as u8, 8 as u8, 1 as u8,),
-            vec!(5 as u8, 7 as u8, 1 as u8, 9 as u8, 8 as u8, 4 as u8, 3 as u8, 6 as u8, 2 as u8,),
-            vec!(2 as u8, 8 as u8, 4 as u8, 1 as u8, 3 as u8, 6 as u8, 5 as u8, 7 as u8, 9 as u8,),
+            vec!(8, 1, 5, 6, 9, 2, 7, 3, 4,),
+            vec!(7, 3, 2, 4, 1, 5, 6, 9, 8,),
+            vec!(6, 4, 9, 3, 7, 8, 1, 2, 5,),
+            vec!(3, 9, 7, 8, 4, 1, 2, 5, 6,),
+            vec!(4, 5, 6, 7, 2, 9, 8, 1, 3,),
+            vec!(1, 2, 8, 5, 6, 3, 9, 4, 7,),
+            vec!(9, 6, 3, 2, 5, 7, 4, 8, 1,),
+            vec!(5, 7, 1, 9, 8, 4, 3, 6, 2,),
+            vec!(2, 8, 4, 1, 3, 6, 5, 7, 9,),
         ),),
         puzzle_2: (vec!(
             vec!(0, 0, 5, 0, 7, 0, 9, 0, 4,),
@@ -493,21 +475,21 @@ mod test {
             vec!(8, 0, 9, 0, 0, 0, 0, 4, 2,),
         ),
         vec!(
-            vec!(1 as u8, 3 as u8, 5 as u8, 2 as u8, 7 as u8, 8 as u8, 9 as u8, 6 as u8, 4 as u8,),
-            vec!(7 as u8, 9 as u8, 8 as u8, 6 as u8, 4 as u8, 5 as u8, 2 as u8, 3 as u8, 1 as u8,),
-            vec!(6 as u8, 4 as u8, 2 as u8, 3 as u8, 9 as u8, 1 as u8, 8 as u8, 5 as u8, 7 as u8,),
-            vec!(5 as u8, 2 as u8, 7 as u8, 4 as u8, 6 as u8, 3 as u8, 1 as u8, 9 as u8, 8 as u8,),
-            vec!(9 as u8, 1 as u8, 6 as u8, 5 as u8, 8 as u8, 2 as u8, 4 as u8, 7 as u8, 3 as u8,),
-            vec!(4 as u8, 8 as u8, 3 as u8, 7 as u8, 1 as u8, 9 as u8, 5 as u8, 2 as u8, 6 as u8,),
-            vec!(2 as u8, 6 as u8, 1 as u8, 9 as u8, 3 as u8, 4 as u8, 7 as u8, 8 as u8, 5 as u8,),
-            vec!(3 as u8, 5 as u8, 4 as u8, 8 as u8, 2 as u8, 7 as u8, 6 as u8, 1 as u8, 9 as u8,),
-            vec!(8 as u8, 7 as u8, 9 as u8, 1 as u8, 5 as u8, 6 as u8, 3 as u8, 4 as u8, 2 as u8,), 
+            vec!(1, 3, 5, 2, 7, 8, 9, 6, 4,),
+            vec!(7, 9, 8, 6, 4, 5, 2, 3, 1,),
+            vec!(6, 4, 2, 3, 9, 1, 8, 5, 7,),
+            vec!(5, 2, 7, 4, 6, 3, 1, 9, 8,),
+            vec!(9, 1, 6, 5, 8, 2, 4, 7, 3,),
+            vec!(4, 8, 3, 7, 1, 9, 5, 2, 6,),
+            vec!(2, 6, 1, 9, 3, 4, 7, 8, 5,),
+            vec!(3, 5, 4, 8, 2, 7, 6, 1, 9,),
+            vec!(8, 7, 9, 1, 5, 6, 3, 4, 2,), 
         ),),
     }
 
     #[test]
     fn test_unsolvable_puzzle() {
-        let mut board = vec!(
+        let mut board = Board::from_rows(&[
             vec!(0, 0, 5, 0, 7, 0, 9, 0, 4,),
             vec!(0, 9, 0, 0, 4, 0, 2, 3, 1,),
             vec!(6, 0, 2, 0, 9, 1, 0, 0, 0,),
@@ -517,9 +499,73 @@ mod test {
             vec!(2, 6, 0, 0, 0, 0, 0, 8, 5,),
             vec!(3, 0, 0, 8, 0, 7, 0, 1, 0,),
             vec!(8, 0, 9, 0, 0, 0, 0, 4, 3,),
-        );
+        ]).unwrap();
+
+        assert_eq!(Err(SudokuError::Unsolvable), solve_puzzle(&mut board));
+    }
+
+    macro_rules! test_count_solutions {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (input, limit, expected) = $value;
+                    let board = Board::from_rows(&input).unwrap();
+                    assert_eq!(expected, count_solutions(&board, limit));
+                    assert_eq!(expected == 1, has_unique_solution(&board));
+                }
+            )*
+        }
+    }
 
-        assert_eq!(None, solve_puzzle(&mut board, false));
+    test_count_solutions! {
+        puzzle_1_unique: (vec!(
+            vec!(0, 0, 0, 0, 0, 2, 7, 3, 4,),
+            vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
+            vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 1, 0, 0, 0,),
+            vec!(4, 0, 6, 0, 2, 0, 0, 1, 3,),
+            vec!(0, 0, 8, 0, 0, 0, 9, 4, 0,),
+            vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
+            vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
+        ), 10, 1,),
+        puzzle_2_unique: (vec!(
+            vec!(0, 0, 5, 0, 7, 0, 9, 0, 4,),
+            vec!(0, 9, 0, 0, 4, 0, 2, 3, 1,),
+            vec!(6, 0, 2, 0, 9, 1, 0, 0, 0,),
+            vec!(5, 0, 0, 4, 0, 3, 0, 0, 8,),
+            vec!(0, 1, 6, 5, 0, 2, 0, 0, 0,),
+            vec!(0, 8, 0, 0, 1, 0, 5, 2, 6,),
+            vec!(2, 6, 0, 0, 0, 0, 0, 8, 5,),
+            vec!(3, 0, 0, 8, 0, 7, 0, 1, 0,),
+            vec!(8, 0, 9, 0, 0, 0, 0, 4, 2,),
+        ), 10, 1,),
+        unsolvable_none: (vec!(
+            vec!(0, 0, 5, 0, 7, 0, 9, 0, 4,),
+            vec!(0, 9, 0, 0, 4, 0, 2, 3, 1,),
+            vec!(6, 0, 2, 0, 9, 1, 0, 0, 0,),
+            vec!(5, 0, 0, 4, 0, 3, 0, 0, 8,),
+            vec!(0, 1, 6, 5, 0, 2, 0, 0, 0,),
+            vec!(0, 8, 0, 0, 1, 0, 5, 2, 6,),
+            vec!(2, 6, 0, 0, 0, 0, 0, 8, 5,),
+            vec!(3, 0, 0, 8, 0, 7, 0, 1, 0,),
+            vec!(8, 0, 9, 0, 0, 0, 0, 4, 3,),
+        ), 10, 0,),
+        invalid_none: (vec!(
+            vec!(2, 0, 0, 0, 0, 2, 7, 3, 4,),
+            vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
+            vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 0, 1, 0, 0, 0,),
+            vec!(4, 0, 6, 0, 2, 0, 0, 1, 3,),
+            vec!(0, 0, 8, 0, 0, 0, 9, 4, 0,),
+            vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
+            vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
+        ), 10, 0,),
+        two_solutions: (fixtures::two_solutions().to_rows(), 10, 2,),
+        stops_at_limit: (vec!(vec!(0; 9); 9), 5, 5,),
+        zero_limit: (vec!(vec!(0; 9); 9), 0, 0,),
     }
 
     macro_rules! test_is_valid_move {
@@ -527,7 +573,8 @@ mod test {
             $(
                 #[test]
                 fn $name() {
-                    let (board, pos, num, expected) = $value;
+                    let (input, pos, num, expected) = $value;
+                    let board = Board::from_rows(&input).unwrap();
                     assert_eq!(expected, is_valid_move(&board, &pos, num));
                 }
             )*
@@ -564,4 +611,79 @@ mod test {
         6,
         false,),
     }
+
+    // a solved board for any geometry
+    fn pattern(geometry: Geometry) -> Board {
+        let (box_rows, box_cols, size) = (geometry.box_rows(), geometry.box_cols(), geometry.size());
+        let mut board = Board::empty(geometry);
+
+        for row in 0..size {
+            for col in 0..size {
+                board.set(row, col, (((row % box_rows) * box_cols + row / box_rows + col) % size) as u8 + 1);
+            }
+        }
+
+        board
+    }
+
+    macro_rules! test_solve_other_sizes {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (box_rows, box_cols) = $value;
+                    let solution = pattern(Geometry::new(box_rows, box_cols).unwrap());
+                    assert!(is_valid_board(&solution));
+
+                    let mut puzzle = solution.clone();
+                    for ((row, col), _) in solution.cells() {
+                        if (row * 7 + col * 3) % 5 == 0 {
+                            puzzle.set(row, col, 0);
+                        }
+                    }
+
+                    let mut board = puzzle.clone();
+                    solve_puzzle(&mut board).unwrap();
+
+                    assert!(is_valid_board(&board));
+                    assert_eq!(None, find_empty(&board));
+                    assert!(puzzle.cells().all(|(position, value)| value == 0 || board[position] == value));
+                }
+            )*
+        }
+    }
+
+    test_solve_other_sizes! {
+        four_by_four: (2, 2),
+        six_by_six: (2, 3),
+        six_by_six_tall_boxes: (3, 2),
+        twelve_by_twelve: (3, 4),
+        sixteen_by_sixteen: (4, 4),
+        twenty_five_by_twenty_five: (5, 5),
+    }
+
+    #[test]
+    fn test_count_solutions_four_by_four() {
+        let board = Board::empty(Geometry::new(2, 2).unwrap());
+
+        assert_eq!(288, count_solutions(&board, 1000));
+    }
+
+    #[test]
+    fn test_find_conflicts_four_by_four() {
+        let board = Board::from_rows(&[
+            vec!(1, 0, 0, 5,),
+            vec!(0, 1, 0, 0,),
+            vec!(0, 0, 0, 0,),
+            vec!(0, 0, 0, 0,),
+        ]).unwrap();
+
+        assert_eq!(
+            vec!(
+                Conflict::OutOfRange { row: 0, col: 3, value: 5 },
+                Conflict::Duplicate { unit: Unit::Box(0), value: 1, cells: vec!((0, 0), (1, 1)) },
+            ),
+            find_conflicts(&board)
+        );
+    }
 }
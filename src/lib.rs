@@ -0,0 +1,45 @@
+//! A backtracking sudoku solver.
+
+mod backbone;
+mod backend;
+mod board;
+mod candidates;
+mod constraints;
+mod dlx;
+mod engine;
+mod error;
+#[cfg(test)]
+pub(crate) mod fixtures;
+mod game;
+mod observer;
+mod order;
+mod parallel;
+mod render;
+mod rng;
+mod sat;
+mod selector;
+mod session;
+mod solutions;
+mod solver;
+
+pub use backbone::Backbone;
+pub use backend::{Backend, CrossCheck, SolverBackend};
+pub use board::{Board, Geometry, Unit, BOX_SIZE, MAX_SIZE, SIZE};
+pub use candidates::{digit_bit, digits, Candidates, Digits};
+pub use constraints::{AntiKnight, Constraint, Rules};
+pub use dlx::{DlxSolutions, DlxSolver};
+pub use engine::{SearchEngine, Step};
+pub use error::SudokuError;
+pub use game::{
+    count_solutions, find_conflicts, find_empty, has_unique_solution, is_valid_board, is_valid_move,
+    solve_puzzle, validate_board, Conflict,
+};
+pub use observer::SolveObserver;
+pub use order::{Ascending, Descending, LeastConstrainingValue, RandomOrder, ValueOrder};
+pub use parallel::ParallelSolver;
+pub use render::{Ansi, Classic, Compact, Renderer, Symbols, Unicode};
+pub use sat::{Cnf, SatSolver};
+pub use selector::{CellSelector, FirstEmpty, MinimumRemainingValues, MrvDegree, RandomCell};
+pub use session::{Solvability, SolverSession};
+pub use solutions::{solutions, Solutions};
+pub use solver::{Budget, CancelToken, SolveResult, SolveStats, Solver};
@@ -1,7 +1,9 @@
-mod game;
+use std::io;
+
+use sudoku_solver::{validate_board, Board, Solver};
 
 fn main() {
-    let mut board = vec!(
+    let mut board = Board::from_rows(&[
         vec!(0, 0, 0, 0, 0, 2, 7, 3, 4,),
         vec!(7, 0, 0, 0, 0, 5, 0, 9, 0,),
         vec!(0, 4, 0, 0, 0, 0, 0, 0, 0,),
@@ -11,9 +13,19 @@ fn main() {
         vec!(9, 0, 0, 0, 0, 7, 0, 0, 0,),
         vec!(0, 0, 0, 0, 8, 0, 0, 0, 2,),
         vec!(0, 8, 0, 0, 3, 0, 5, 0, 0,),
-    );
+    ]).unwrap();
 
-    game::print_board(&board);
+    print!("{}", board);
+    if let Err(error) = validate_board(&board) {
+        println!("Board is invalid: {}", error);
+        return;
+    }
     println!("#################################################");
-    game::solve_puzzle(&mut board, true);
-}
\ No newline at end of file
+    match Solver::new().progress(io::stdout()).solve_in_place(&mut board) {
+        Ok(stats) => println!(
+            "Solved after {} nodes and {} guesses in {:?}",
+            stats.nodes, stats.guesses, stats.elapsed
+        ),
+        Err(error) => println!("{}", error),
+    }
+}
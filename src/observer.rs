@@ -0,0 +1,42 @@
+use crate::board::Board;
+
+/// Hears about every step the `Solver` takes. Every method does nothing by
+/// default, so an observer only implements the events it cares about.
+///
+/// The solver is compiled separately for the case with no observer, so
+/// leaving one off costs nothing.
+pub trait SolveObserver {
+    /// A cell was picked and its digits will be tried in this order.
+    fn on_branch(&mut self, _row: usize, _col: usize, _digits: &[u8]) {}
+
+    /// A digit went into a cell, `forced` when propagation put it there
+    /// rather than the search trying it.
+    fn on_place(&mut self, _row: usize, _col: usize, _digit: u8, _forced: bool) {}
+
+    /// A digit was taken back out of a cell.
+    fn on_undo(&mut self, _row: usize, _col: usize, _digit: u8) {}
+
+    fn on_solution(&mut self, _board: &Board) {}
+}
+
+/// No observer at all.
+impl SolveObserver for () {}
+
+/// Lets the caller keep hold of an observer and read it after the solve.
+impl<T: SolveObserver + ?Sized> SolveObserver for &mut T {
+    fn on_branch(&mut self, row: usize, col: usize, digits: &[u8]) {
+        (**self).on_branch(row, col, digits);
+    }
+
+    fn on_place(&mut self, row: usize, col: usize, digit: u8, forced: bool) {
+        (**self).on_place(row, col, digit, forced);
+    }
+
+    fn on_undo(&mut self, row: usize, col: usize, digit: u8) {
+        (**self).on_undo(row, col, digit);
+    }
+
+    fn on_solution(&mut self, board: &Board) {
+        (**self).on_solution(board);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::candidates::{digit_bit, digits, Candidates};
+use crate::rng::Rng;
+
+/// Chooses the order the solver tries a cell's legal digits in.
+pub trait ValueOrder {
+    /// Fills `order` with every digit in `mask`, the legal digits for the
+    /// cell at `position`, in the order they should be tried.
+    fn order(&mut self, candidates: &Candidates, position: (usize, usize), mask: u32, order: &mut Vec<u8>);
+}
+
+/// Smallest digit first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Ascending;
+
+impl ValueOrder for Ascending {
+    fn order(&mut self, _: &Candidates, _: (usize, usize), mask: u32, order: &mut Vec<u8>) {
+        order.extend(digits(mask));
+    }
+}
+
+/// Largest digit first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Descending;
+
+impl ValueOrder for Descending {
+    fn order(&mut self, _: &Candidates, _: (usize, usize), mask: u32, order: &mut Vec<u8>) {
+        order.extend(digits(mask));
+        order.reverse();
+    }
+}
+
+/// The digit that rules out the fewest candidates among the cell's empty
+/// peers under the candidates' rules first, ties going to the smaller digit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LeastConstrainingValue;
+
+impl ValueOrder for LeastConstrainingValue {
+    fn order(&mut self, candidates: &Candidates, (row, col): (usize, usize), mask: u32, order: &mut Vec<u8>) {
+        let peers: Vec<u32> = candidates.rules().peers(row, col).iter().map(|&(r, c)| candidates.get(r, c)).collect();
+
+        order.extend(digits(mask));
+        // a stable sort keeps ascending order between digits that tie
+        order.sort_by_key(|&digit| peers.iter().filter(|&&peer| peer & digit_bit(digit) != 0).count());
+    }
+}
+
+/// The digits in a random order, the same seed always gives the same orders.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RandomOrder {
+    rng: Rng,
+}
+
+impl RandomOrder {
+    pub fn new(seed: u64) -> RandomOrder {
+        RandomOrder { rng: Rng::new(seed) }
+    }
+}
+
+impl ValueOrder for RandomOrder {
+    fn order(&mut self, _: &Candidates, _: (usize, usize), mask: u32, order: &mut Vec<u8>) {
+        order.extend(digits(mask));
+
+        // fisher-yates
+        for i in (1..order.len()).rev() {
+            order.swap(i, self.rng.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::board::Board;
+    use crate::constraints::Rules;
+
+    fn ordered<O: ValueOrder>(value_order: &mut O, candidates: &Candidates, position: (usize, usize)) -> Vec<u8> {
+        let mut order = Vec::new();
+        value_order.order(candidates, position, candidates.get(position.0, position.1), &mut order);
+
+        order
+    }
+
+    #[test]
+    fn test_ascending_and_descending() {
+        let mut board = Board::new();
+        board.set(0, 1, 2);
+        board.set(0, 2, 5);
+        let candidates = Candidates::new(&board).unwrap();
+
+        assert_eq!(vec!(1, 3, 4, 6, 7, 8, 9), ordered(&mut Ascending, &candidates, (0, 0)));
+        assert_eq!(vec!(9, 8, 7, 6, 4, 3, 1), ordered(&mut Descending, &candidates, (0, 0)));
+    }
+
+    #[test]
+    fn test_least_constraining_value() {
+        let mut board = Board::new();
+        // each of these rules its digit out of six peers of (0, 0)
+        board.set(1, 4, 9);
+        board.set(4, 1, 8);
+        let candidates = Candidates::new(&board).unwrap();
+
+        assert_eq!(vec!(8, 9, 1, 2, 3, 4, 5, 6, 7), ordered(&mut LeastConstrainingValue, &candidates, (0, 0)));
+
+        // and this one rules 9 out of two more
+        board.set(7, 2, 9);
+        let candidates = Candidates::new(&board).unwrap();
+
+        assert_eq!(vec!(9, 8, 1, 2, 3, 4, 5, 6, 7), ordered(&mut LeastConstrainingValue, &candidates, (0, 0)));
+    }
+
+    #[test]
+    fn test_least_constraining_value_uses_rules() {
+        let mut board = Board::new();
+        // both rule their digit out of one cell in row 0 and one in column 0,
+        // but on the diagonal the 9 rules out (3, 3), (4, 4) and (5, 5) while
+        // the 8 only rules out (5, 5) and (7, 7)
+        board.set(3, 4, 9);
+        board.set(5, 7, 8);
+        let classic = Candidates::new(&board).unwrap();
+        let diagonal = Candidates::with_rules(&board, Arc::new(Rules::diagonal(board.geometry()))).unwrap();
+
+        assert_eq!(vec!(8, 9, 1, 2, 3, 4, 5, 6, 7), ordered(&mut LeastConstrainingValue, &classic, (0, 0)));
+        assert_eq!(vec!(9, 8, 1, 2, 3, 4, 5, 6, 7), ordered(&mut LeastConstrainingValue, &diagonal, (0, 0)));
+    }
+
+    #[test]
+    fn test_random_order_is_seeded() {
+        let candidates = Candidates::new(&Board::new()).unwrap();
+        let shuffled = |seed| {
+            let mut value_order = RandomOrder::new(seed);
+            (0..5).map(|_| ordered(&mut value_order, &candidates, (0, 0))).collect::<Vec<Vec<u8>>>()
+        };
+
+        assert_eq!(shuffled(5), shuffled(5));
+        assert_ne!(shuffled(5), shuffled(6));
+
+        for mut order in shuffled(5) {
+            order.sort_unstable();
+            assert_eq!(vec!(1, 2, 3, 4, 5, 6, 7, 8, 9), order);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::board::Board;
+use crate::candidates::{digits, each_solution, Candidates};
+use crate::error::SudokuError;
+use crate::solver::{CancelToken, SolveResult, SolveStats, Solver};
+
+// how many subtrees to aim for per thread when the split depth isn't set, so
+// a thread that draws easy ones can pick up more
+const TASKS_PER_THREAD: usize = 16;
+
+/// Splits the backtracking search into subtrees near the root and works on
+/// them from several threads. The first solution is the one the sequential
+/// `Solver` finds with its default options, and counts are the same as
+/// `count_solutions`.
+///
+/// ```
+/// use sudoku_solver::{Board, ParallelSolver, Solver};
+///
+/// let board = Board::new();
+/// let parallel = ParallelSolver::new().threads(4).solve(&board).solution;
+///
+/// assert_eq!(Solver::new().solve(&board).solution, parallel);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParallelSolver {
+    threads: usize,
+    split_depth: Option<usize>,
+}
+
+impl ParallelSolver {
+    /// Uses as many threads as the machine has cores.
+    pub fn new() -> ParallelSolver {
+        ParallelSolver {
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            split_depth: None,
+        }
+    }
+
+    pub fn threads(mut self, threads: usize) -> ParallelSolver {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Fills this many cells before handing the subtrees to the threads. By
+    /// default cells are filled until there are plenty of subtrees to share.
+    pub fn split_depth(mut self, depth: usize) -> ParallelSolver {
+        self.split_depth = Some(depth);
+        self
+    }
+
+    /// Solves a copy of the board, leaving the original untouched.
+    pub fn solve(&self, board: &Board) -> SolveResult {
+        let start = Instant::now();
+        let (tasks, mut stats) = match self.split(board) {
+            Ok(split) => split,
+            Err(error) => return SolveResult { solution: Err(error), stats: SolveStats::default() },
+        };
+
+        let tokens: Vec<CancelToken> = tasks.iter().map(|_| CancelToken::new()).collect();
+        let next = AtomicUsize::new(0);
+        // the earliest subtree with a solution so far and that solution
+        let best: Mutex<Option<(usize, Board)>> = Mutex::new(None);
+        let totals = Mutex::new(SolveStats::default());
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(tasks.len()) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= tasks.len() {
+                        break;
+                    }
+
+                    // a subtree after one that is already solved can't win
+                    if best.lock().unwrap().as_ref().is_some_and(|&(won, _)| won < index) {
+                        continue;
+                    }
+
+                    let result = Solver::new().cancel_token(tokens[index].clone()).solve(&tasks[index]);
+                    add(&mut totals.lock().unwrap(), &result.stats);
+
+                    if let Ok(solution) = result.solution {
+                        let mut best = best.lock().unwrap();
+                        if best.as_ref().is_none_or(|&(won, _)| index < won) {
+                            *best = Some((index, solution));
+                            tokens[index + 1..].iter().for_each(CancelToken::cancel);
+                        }
+                    }
+                });
+            }
+        });
+
+        let totals = totals.into_inner().unwrap();
+        stats.max_depth += totals.max_depth;
+        add(&mut stats, &totals);
+        stats.elapsed = start.elapsed();
+
+        let solution = match best.into_inner().unwrap() {
+            Some((_, solution)) => Ok(solution),
+            None => Err(SudokuError::Unsolvable),
+        };
+
+        SolveResult { solution, stats }
+    }
+
+    /// Solves the board in place. On failure the board is left as it was.
+    pub fn solve_in_place(&self, board: &mut Board) -> Result<SolveStats, SudokuError> {
+        let result = self.solve(board);
+        *board = result.solution?;
+
+        Ok(result.stats)
+    }
+
+    /// Counts the solutions of a board, stopping once `limit` have been found.
+    /// Invalid boards have no solutions.
+    pub fn count_solutions(&self, board: &Board, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+
+        let tasks = match self.split(board) {
+            Ok((tasks, _)) => tasks,
+            Err(_) => return 0,
+        };
+
+        let next = AtomicUsize::new(0);
+        let found = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(tasks.len()) {
+                scope.spawn(|| {
+                    while !stop.load(Ordering::Relaxed) {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= tasks.len() {
+                            break;
+                        }
+
+                        let mut candidates = Candidates::new(&tasks[index]).unwrap();
+                        each_solution(&mut candidates, &stop, &mut |_| {
+                            if found.fetch_add(1, Ordering::Relaxed) + 1 >= limit {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                        });
+                    }
+                });
+            }
+        });
+
+        found.into_inner().min(limit)
+    }
+
+    // the partial boards left after filling the first empty cells with every
+    // legal digit, in the order the sequential search would reach them
+    fn split(&self, board: &Board) -> Result<(Vec<Board>, SolveStats), SudokuError> {
+        let mut tasks = vec![Candidates::new(board)?];
+        let mut stats = SolveStats::default();
+        let enough = self.threads * TASKS_PER_THREAD;
+
+        while self.split_depth.map_or(tasks.len() < enough, |depth| stats.max_depth < depth) {
+            let mut expanded = Vec::new();
+            let mut filled = false;
+
+            for task in tasks {
+                let first = task.iter().next().map(|(position, _)| position);
+                let (row, col) = match first {
+                    Some(position) => position,
+                    None => {
+                        expanded.push(task);
+                        continue;
+                    }
+                };
+
+                stats.nodes += 1;
+                let mask = task.get(row, col);
+                for digit in digits(mask) {
+                    let mut child = task.clone();
+                    child.fill(row, col, digit);
+                    expanded.push(child);
+
+                    filled = true;
+                    stats.placements += 1;
+                    if mask.count_ones() > 1 {
+                        stats.guesses += 1;
+                    }
+                }
+            }
+
+            tasks = expanded;
+            if !filled {
+                break;
+            }
+            stats.max_depth += 1;
+        }
+
+        Ok((tasks.iter().map(|task| task.board().clone()).collect(), stats))
+    }
+}
+
+impl Default for ParallelSolver {
+    fn default() -> ParallelSolver {
+        ParallelSolver::new()
+    }
+}
+
+fn add(total: &mut SolveStats, stats: &SolveStats) {
+    total.nodes += stats.nodes;
+    total.placements += stats.placements;
+    total.propagated += stats.propagated;
+    total.backtracks += stats.backtracks;
+    total.max_depth = total.max_depth.max(stats.max_depth);
+    total.guesses += stats.guesses;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::{two_solutions, unsolvable};
+    use crate::game::count_solutions;
+
+    macro_rules! test_matches_sequential {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let board = $value;
+                    let expected = Solver::new().solve(&board).solution;
+
+                    for &threads in &[1, 3, 8] {
+                        let parallel = ParallelSolver::new().threads(threads);
+                        assert_eq!(expected, parallel.solve(&board).solution);
+                        assert_eq!(expected, parallel.split_depth(2).solve(&board).solution);
+                        assert_eq!(count_solutions(&board, 50), parallel.count_solutions(&board, 50));
+                    }
+                }
+            )*
+        }
+    }
+
+    test_matches_sequential! {
+        empty_board: Board::new(),
+        two_solution_board: two_solutions(),
+        six_by_six: Board::empty(Geometry::new(2, 3).unwrap()),
+        sixteen_by_sixteen: Board::empty(Geometry::new(4, 4).unwrap()),
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let board = Board::empty(Geometry::new(2, 2).unwrap());
+        let parallel = ParallelSolver::new().threads(4);
+
+        assert_eq!(288, parallel.count_solutions(&board, 1000));
+        assert_eq!(100, parallel.count_solutions(&board, 100));
+        assert_eq!(0, parallel.count_solutions(&board, 0));
+        assert_eq!(2, parallel.count_solutions(&two_solutions(), 10));
+    }
+
+    #[test]
+    fn test_invalid_and_unsolvable() {
+        let mut board = two_solutions();
+        board.set(0, 2, 1);
+
+        assert!(matches!(ParallelSolver::new().solve(&board).solution, Err(SudokuError::DuplicateInUnit { .. })));
+        assert_eq!(0, ParallelSolver::new().count_solutions(&board, 10));
+
+        let board = unsolvable();
+        let mut in_place = board.clone();
+
+        assert_eq!(Err(SudokuError::Unsolvable), ParallelSolver::new().threads(4).split_depth(3).solve_in_place(&mut in_place));
+        assert_eq!(board, in_place);
+    }
+
+    #[test]
+    fn test_solved_board() {
+        let mut board = two_solutions();
+        board.set(0, 2, 5);
+        board.set(0, 5, 8);
+        board.set(1, 2, 8);
+        board.set(1, 5, 5);
+
+        assert_eq!(Ok(board.clone()), ParallelSolver::new().solve(&board).solution);
+        assert_eq!(1, ParallelSolver::new().count_solutions(&board, 10));
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::board::Board;
+use crate::game::{find_conflicts, Conflict};
+
+const RESET: &str = "\x1b[0m";
+const GIVEN: &str = "\x1b[1m";
+const FILLED: &str = "\x1b[36m";
+const CONFLICT: &str = "\x1b[1;31m";
+
+/// Draws a board to any writer. Invalid boards are drawn too, with the cells
+/// in conflict marked wherever the style allows.
+pub trait Renderer {
+    fn render(&self, board: &Board, out: &mut dyn Write) -> io::Result<()>;
+}
+
+/// How values are written out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symbols {
+    /// 1 to 9 followed by letters, so a 16 x 16 board uses 1-9 and A-G and
+    /// every cell stays one character wide.
+    #[default]
+    Alphanumeric,
+    /// Plain numbers, padded to two characters on boards larger than 9 x 9.
+    Decimal,
+}
+
+impl Symbols {
+    fn width(&self, size: usize) -> usize {
+        match self {
+            Symbols::Alphanumeric => 1,
+            Symbols::Decimal => size.to_string().len(),
+        }
+    }
+
+    fn text(&self, value: u8, size: usize, empty: char) -> String {
+        let text = match (self, value) {
+            (_, 0) => empty.to_string(),
+            (Symbols::Alphanumeric, 1..=9) => value.to_string(),
+            (Symbols::Alphanumeric, 10..=35) => ((b'A' + value - 10) as char).to_string(),
+            (Symbols::Alphanumeric, _) => "?".to_string(),
+            (Symbols::Decimal, _) => value.to_string(),
+        };
+
+        format!("{:>width$}", text, width = self.width(size))
+    }
+}
+
+/// Every cell on a single line with `.` for empty cells.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Compact {
+    symbols: Symbols,
+}
+
+/// The original ASCII grid, with cells in conflict marked by a `*`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Classic {
+    symbols: Symbols,
+}
+
+/// A grid drawn with Unicode box-drawing characters, with cells in conflict
+/// marked by a `*`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Unicode {
+    symbols: Symbols,
+}
+
+/// A grid coloured with ANSI escape codes: givens in bold, filled in digits
+/// in cyan and cells in conflict in red.
+#[derive(Clone, Debug, Default)]
+pub struct Ansi {
+    givens: Option<Board>,
+    symbols: Symbols,
+}
+
+impl Compact {
+    pub fn symbols(mut self, symbols: Symbols) -> Compact {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl Classic {
+    pub fn symbols(mut self, symbols: Symbols) -> Classic {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl Unicode {
+    pub fn symbols(mut self, symbols: Symbols) -> Unicode {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl Ansi {
+    /// Treats every filled cell as a given.
+    pub fn new() -> Ansi {
+        Ansi::default()
+    }
+
+    /// Treats the filled cells of `puzzle` as givens and everything else as
+    /// filled in. Boards of another shape than `puzzle` have no givens.
+    pub fn with_givens(puzzle: &Board) -> Ansi {
+        Ansi {
+            givens: Some(puzzle.clone()),
+            symbols: Symbols::default(),
+        }
+    }
+
+    pub fn symbols(mut self, symbols: Symbols) -> Ansi {
+        self.symbols = symbols;
+        self
+    }
+}
+
+impl Renderer for Compact {
+    fn render(&self, board: &Board, out: &mut dyn Write) -> io::Result<()> {
+        let separator = if self.symbols.width(board.size()) > 1 { " " } else { "" };
+        let cells: Vec<String> = board
+            .cells()
+            .map(|(_, value)| self.symbols.text(value, board.size(), '.'))
+            .collect();
+
+        writeln!(out, "{}", cells.join(separator))
+    }
+}
+
+impl Renderer for Classic {
+    fn render(&self, board: &Board, out: &mut dyn Write) -> io::Result<()> {
+        let style = GridStyle {
+            top: None,
+            middle: ('|', '-', '+', '|'),
+            bottom: None,
+            vertical: '|',
+            empty: '0',
+            symbols: self.symbols,
+        };
+        let conflicts = conflict_cells(board);
+
+        write_grid(out, board, &style, |position, text| {
+            let mark = if conflicts.contains(&position) { '*' } else { ' ' };
+            format!("{}{}", mark, text)
+        })
+    }
+}
+
+impl Renderer for Unicode {
+    fn render(&self, board: &Board, out: &mut dyn Write) -> io::Result<()> {
+        let style = GridStyle {
+            top: Some(('┌', '─', '┬', '┐')),
+            middle: ('├', '─', '┼', '┤'),
+            bottom: Some(('└', '─', '┴', '┘')),
+            vertical: '│',
+            empty: '.',
+            symbols: self.symbols,
+        };
+        let conflicts = conflict_cells(board);
+
+        write_grid(out, board, &style, |position, text| {
+            let mark = if conflicts.contains(&position) { '*' } else { ' ' };
+            format!("{}{}", mark, text)
+        })
+    }
+}
+
+impl Renderer for Ansi {
+    fn render(&self, board: &Board, out: &mut dyn Write) -> io::Result<()> {
+        let style = GridStyle {
+            top: None,
+            middle: ('|', '-', '+', '|'),
+            bottom: None,
+            vertical: '|',
+            empty: '.',
+            symbols: self.symbols,
+        };
+        let conflicts = conflict_cells(board);
+
+        write_grid(out, board, &style, |position, text| {
+            let given = match &self.givens {
+                Some(givens) => givens.geometry() == board.geometry() && givens[position] != 0,
+                None => true,
+            };
+
+            if board[position] == 0 {
+                format!(" {}", text)
+            } else if conflicts.contains(&position) {
+                format!(" {}{}{}", CONFLICT, text, RESET)
+            } else if given {
+                format!(" {}{}{}", GIVEN, text, RESET)
+            } else {
+                format!(" {}{}{}", FILLED, text, RESET)
+            }
+        })
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut out = Vec::new();
+        Classic::default().render(self, &mut out).map_err(|_| fmt::Error)?;
+
+        f.write_str(&String::from_utf8_lossy(&out))
+    }
+}
+
+// the left edge, fill, box crossing and right edge of a horizontal line
+type Rule = (char, char, char, char);
+
+struct GridStyle {
+    top: Option<Rule>,
+    middle: Rule,
+    bottom: Option<Rule>,
+    vertical: char,
+    empty: char,
+    symbols: Symbols,
+}
+
+// a horizontal line as wide as a row of the grid
+fn rule(board: &Board, style: &GridStyle, (left, fill, cross, right): Rule) -> String {
+    let geometry = board.geometry();
+    let width = style.symbols.width(board.size());
+    let segment: String = std::iter::repeat_n(fill, geometry.box_cols() * (width + 1) + 1).collect();
+    let segments = vec![segment; geometry.box_rows()];
+
+    format!("{}{}{}", left, segments.join(&cross.to_string()), right)
+}
+
+// writes the rows of the grid, `cell` turns a symbol into the text shown for
+// that cell
+fn write_grid<F>(out: &mut dyn Write, board: &Board, style: &GridStyle, cell: F) -> io::Result<()>
+where
+    F: Fn((usize, usize), String) -> String,
+{
+    let size = board.size();
+    let geometry = board.geometry();
+
+    if let Some(top) = style.top {
+        writeln!(out, "{}", rule(board, style, top))?;
+    }
+
+    for row in 0..size {
+        if row % geometry.box_rows() == 0 && row != 0 {
+            writeln!(out, "{}", rule(board, style, style.middle))?;
+        }
+
+        for col in 0..size {
+            if col % geometry.box_cols() == 0 {
+                write!(out, "{}", style.vertical)?;
+            }
+
+            let text = style.symbols.text(board[(row, col)], size, style.empty);
+            write!(out, "{}", cell((row, col), text))?;
+
+            if col % geometry.box_cols() == geometry.box_cols() - 1 {
+                write!(out, " ")?;
+            }
+        }
+
+        writeln!(out, "{}", style.vertical)?;
+    }
+
+    if let Some(bottom) = style.bottom {
+        writeln!(out, "{}", rule(board, style, bottom))?;
+    }
+
+    Ok(())
+}
+
+fn conflict_cells(board: &Board) -> HashSet<(usize, usize)> {
+    let mut cells = HashSet::new();
+
+    for conflict in find_conflicts(board) {
+        match conflict {
+            Conflict::OutOfRange { row, col, .. } | Conflict::Constraint { row, col, .. } => {
+                cells.insert((row, col));
+            }
+            Conflict::Duplicate { cells: duplicates, .. } => cells.extend(duplicates),
+        }
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::fixtures::puzzle;
+
+    fn render(renderer: &dyn Renderer, board: &Board) -> String {
+        let mut out = Vec::new();
+        renderer.render(board, &mut out).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_compact() {
+        assert_eq!(
+            ".....2734\
+             7....5.9.\
+             .4.......\
+             .....1...\
+             4.6.2..13\
+             ..8...94.\
+             9....7...\
+             ....8...2\
+             .8..3.5..\n",
+            render(&Compact::default(), &puzzle())
+        );
+    }
+
+    #[test]
+    fn test_classic() {
+        let expected = "\
+| 0 0 0 | 0 0 2 | 7 3 4 |
+| 7 0 0 | 0 0 5 | 0 9 0 |
+| 0 4 0 | 0 0 0 | 0 0 0 |
+|-------+-------+-------|
+| 0 0 0 | 0 0 1 | 0 0 0 |
+| 4 0 6 | 0 2 0 | 0 1 3 |
+| 0 0 8 | 0 0 0 | 9 4 0 |
+|-------+-------+-------|
+| 9 0 0 | 0 0 7 | 0 0 0 |
+| 0 0 0 | 0 8 0 | 0 0 2 |
+| 0 8 0 | 0 3 0 | 5 0 0 |
+";
+
+        assert_eq!(expected, render(&Classic::default(), &puzzle()));
+        assert_eq!(expected, puzzle().to_string());
+    }
+
+    #[test]
+    fn test_unicode() {
+        let expected = "\
+┌───────┬───────┬───────┐
+│ . . . │ . . 2 │ 7 3 4 │
+│ 7 . . │ . . 5 │ . 9 . │
+│ . 4 . │ . . . │ . . . │
+├───────┼───────┼───────┤
+│ . . . │ . . 1 │ . . . │
+│ 4 . 6 │ . 2 . │ . 1 3 │
+│ . . 8 │ . . . │ 9 4 . │
+├───────┼───────┼───────┤
+│ 9 . . │ . . 7 │ . . . │
+│ . . . │ . 8 . │ . . 2 │
+│ . 8 . │ . 3 . │ 5 . . │
+└───────┴───────┴───────┘
+";
+
+        assert_eq!(expected, render(&Unicode::default(), &puzzle()));
+    }
+
+    #[test]
+    fn test_invalid_board_marks_conflicts() {
+        let mut board = puzzle();
+        board.set(0, 0, 2);
+
+        let rendered = render(&Classic::default(), &board);
+        assert_eq!("|*2 0 0 | 0 0*2 | 7 3 4 |", rendered.lines().next().unwrap());
+        assert_eq!(11, rendered.lines().count());
+    }
+
+    #[test]
+    fn test_ansi() {
+        let mut solved = puzzle();
+        solved.set(0, 0, 8);
+        solved.set(0, 1, 8);
+
+        let first_row = render(&Ansi::with_givens(&puzzle()), &solved);
+        let first_row = first_row.lines().next().unwrap();
+
+        assert!(first_row.starts_with(&format!("| {}8{} {}8{} .", CONFLICT, RESET, CONFLICT, RESET)));
+        assert!(first_row.contains(&format!(" {}2{}", GIVEN, RESET)));
+
+        solved.set(0, 1, 1);
+        let first_row = render(&Ansi::with_givens(&puzzle()), &solved);
+        assert!(first_row.starts_with(&format!("| {}8{} {}1{} .", FILLED, RESET, FILLED, RESET)));
+    }
+
+    #[test]
+    fn test_ansi_givens_of_another_size() {
+        let board = Board::from_rows(&[
+            vec!(1, 2, 3, 4,),
+            vec!(3, 4, 1, 2,),
+            vec!(2, 1, 4, 3,),
+            vec!(4, 3, 2, 1,),
+        ]).unwrap();
+
+        let rendered = render(&Ansi::with_givens(&puzzle()), &board);
+        assert!(rendered.starts_with(&format!("| {}1{} {}2{} |", FILLED, RESET, FILLED, RESET)));
+        assert!(!rendered.contains(GIVEN));
+
+        let rendered = render(&Ansi::with_givens(&board), &puzzle());
+        assert!(!rendered.contains(GIVEN));
+    }
+
+    #[test]
+    fn test_six_by_six() {
+        let board = Board::from_rows(&[
+            vec!(1, 2, 3, 4, 5, 6,),
+            vec!(4, 5, 6, 1, 2, 3,),
+            vec!(2, 3, 1, 5, 6, 4,),
+            vec!(5, 6, 4, 2, 3, 1,),
+            vec!(3, 1, 2, 6, 4, 5,),
+            vec!(6, 4, 5, 3, 1, 0,),
+        ]).unwrap();
+
+        assert_eq!(
+            "\
+┌───────┬───────┐
+│ 1 2 3 │ 4 5 6 │
+│ 4 5 6 │ 1 2 3 │
+├───────┼───────┤
+│ 2 3 1 │ 5 6 4 │
+│ 5 6 4 │ 2 3 1 │
+├───────┼───────┤
+│ 3 1 2 │ 6 4 5 │
+│ 6 4 5 │ 3 1 . │
+└───────┴───────┘
+",
+            render(&Unicode::default(), &board)
+        );
+    }
+
+    #[test]
+    fn test_sixteen_by_sixteen_symbols() {
+        let mut board = Board::empty(Geometry::new(4, 4).unwrap());
+        for col in 0..16 {
+            board.set(0, col, col as u8 + 1);
+        }
+
+        let letters = render(&Compact::default(), &board);
+        assert!(letters.starts_with("123456789ABCDEFG...."));
+        assert_eq!(257, letters.len());
+
+        let numbers = render(&Compact::default().symbols(Symbols::Decimal), &board);
+        assert!(numbers.starts_with(" 1  2  3  4  5  6  7  8  9 10 11 12 13 14 15 16  .  ."));
+
+        let grid = render(&Classic::default().symbols(Symbols::Decimal), &board);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!("|  1  2  3  4 |  5  6  7  8 |  9 10 11 12 | 13 14 15 16 |", lines[0]);
+        assert_eq!("|-------------+-------------+-------------+-------------|", lines[4]);
+        assert_eq!(19, lines.len());
+    }
+}
@@ -0,0 +1,63 @@
+/// A small seeded xorshift generator. It is good enough to shuffle a search
+/// and gives the same sequence for the same seed on every platform.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        // splitmix the seed so nearby seeds start far apart, and so a zero
+        // seed doesn't leave xorshift stuck at zero
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+
+        Rng { state: z.max(1) }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number from `0` up to but not including `bound`, which must not be 0.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        // the modulo bias is far too small to matter for boards this size
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut c = Rng::new(8);
+
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<u64>>());
+        assert_ne!(first, (0..5).map(|_| c.next_u64()).collect::<Vec<u64>>());
+    }
+
+    #[test]
+    fn test_below() {
+        let mut rng = Rng::new(0);
+        let mut seen = [false; 6];
+
+        for _ in 0..200 {
+            seen[rng.below(6)] = true;
+        }
+
+        assert!(seen.iter().all(|&hit| hit));
+    }
+}
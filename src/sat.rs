@@ -0,0 +1,694 @@
+use std::fmt;
+use std::time::Instant;
+
+use crate::board::{Board, Geometry};
+use crate::error::SudokuError;
+use crate::game::validate_board;
+use crate::solver::{Budget, SolveResult, SolveStats};
+
+/// A board as a formula in conjunctive normal form. Variable
+/// `(row * size + col) * size + digit` is true when `digit` goes in the cell.
+/// Every cell holds exactly one digit, every row, column and box holds each
+/// digit exactly once, and every given is a unit clause.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cnf {
+    geometry: Geometry,
+    clauses: Vec<Vec<i32>>,
+}
+
+impl Cnf {
+    pub fn encode(board: &Board) -> Result<Cnf, SudokuError> {
+        validate_board(board)?;
+
+        let size = board.size();
+        let mut cnf = Cnf {
+            geometry: board.geometry(),
+            clauses: Vec::new(),
+        };
+
+        for (row, col) in board.cells().map(|(position, _)| position) {
+            let cell: Vec<i32> = (1..=size as u8).map(|digit| cnf.variable(row, col, digit)).collect();
+            cnf.exactly_one(&cell);
+        }
+
+        for unit in board.units() {
+            let cells = board.unit_cells(unit);
+
+            for digit in 1..=size as u8 {
+                let places: Vec<i32> = cells.iter().map(|&(row, col)| cnf.variable(row, col, digit)).collect();
+                cnf.exactly_one(&places);
+            }
+        }
+
+        for ((row, col), value) in board.cells() {
+            if value != 0 {
+                cnf.clauses.push(vec![cnf.variable(row, col, value)]);
+            }
+        }
+
+        Ok(cnf)
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// The variable for `digit` going in a cell, numbered from 1 as in DIMACS.
+    pub fn variable(&self, row: usize, col: usize, digit: u8) -> i32 {
+        let size = self.geometry.size();
+
+        ((row * size + col) * size + digit as usize) as i32
+    }
+
+    pub fn variables(&self) -> usize {
+        self.geometry.size().pow(3)
+    }
+
+    pub fn clauses(&self) -> &[Vec<i32>] {
+        &self.clauses
+    }
+
+    /// Reads the board back out of a model, either the `s` and `v` lines a
+    /// SAT solver prints or just the literals. Cells whose variables are all
+    /// false or missing are left empty.
+    pub fn read_model(&self, model: &str) -> Result<Board, SudokuError> {
+        let size = self.geometry.size();
+        let mut board = Board::empty(self.geometry);
+
+        for line in model.lines().map(str::trim) {
+            let literals = match line.split_whitespace().next() {
+                None | Some("c") => continue,
+                Some("s") if line.contains("UNSATISFIABLE") => return Err(SudokuError::Unsolvable),
+                Some("s") => continue,
+                Some("v") => &line[1..],
+                Some(_) => line,
+            };
+
+            for literal in literals.split_whitespace() {
+                let literal: i64 = literal.parse().map_err(|_| invalid(format!("{:?} is not a literal", literal)))?;
+                if literal <= 0 {
+                    continue;
+                }
+                if literal as usize > self.variables() {
+                    return Err(invalid(format!("there is no variable {}", literal)));
+                }
+
+                let index = literal as usize - 1;
+                let (row, col, digit) = (index / size / size, index / size % size, (index % size) as u8 + 1);
+                if board[(row, col)] != 0 {
+                    return Err(invalid(format!("({}, {}) is both {} and {}", row, col, board[(row, col)], digit)));
+                }
+                board[(row, col)] = digit;
+            }
+        }
+
+        Ok(board)
+    }
+
+    // a clause for at least one and a clause per pair for at most one
+    fn exactly_one(&mut self, variables: &[i32]) {
+        self.clauses.push(variables.to_vec());
+
+        for (i, &first) in variables.iter().enumerate() {
+            for &second in &variables[i + 1..] {
+                self.clauses.push(vec![-first, -second]);
+            }
+        }
+    }
+}
+
+fn invalid(reason: String) -> SudokuError {
+    SudokuError::InvalidModel { reason }
+}
+
+/// Writes the formula in DIMACS CNF.
+impl fmt::Display for Cnf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let size = self.geometry.size();
+        writeln!(f, "c sudoku {} x {}", size, size)?;
+        writeln!(f, "p cnf {} {}", self.variables(), self.clauses.len())?;
+
+        for clause in &self.clauses {
+            for literal in clause {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Solves a board by encoding it with `Cnf` and handing it to a small built
+/// in conflict driven clause learning solver. In the stats a node is a
+/// decision, and a backtrack is a conflict.
+///
+/// ```
+/// use sudoku_solver::{Board, SatSolver};
+///
+/// let solved = SatSolver::new().solve(&Board::new()).solution.unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SatSolver {
+    node_limit: Option<u64>,
+}
+
+impl SatSolver {
+    pub fn new() -> SatSolver {
+        SatSolver::default()
+    }
+
+    /// Gives up once the solver has made this many decisions.
+    pub fn node_limit(mut self, limit: u64) -> SatSolver {
+        self.node_limit = Some(limit);
+        self
+    }
+
+    /// Solves a copy of the board, leaving the original untouched.
+    pub fn solve(&mut self, board: &Board) -> SolveResult {
+        let start = Instant::now();
+        let cnf = match Cnf::encode(board) {
+            Ok(cnf) => cnf,
+            Err(error) => return SolveResult { solution: Err(error), stats: SolveStats::default() },
+        };
+
+        let mut cdcl = Cdcl::load(&cnf);
+        let solution = cdcl.solve(self.node_limit).map(|model| {
+            let mut solution = Board::empty(cnf.geometry());
+            let size = solution.size();
+
+            for (index, _) in model.iter().enumerate().filter(|&(_, &value)| value) {
+                solution.set(index / size / size, index / size % size, (index % size) as u8 + 1);
+            }
+
+            solution
+        });
+
+        let mut stats = cdcl.stats;
+        stats.elapsed = start.elapsed();
+
+        SolveResult { solution, stats }
+    }
+
+    /// Solves the board in place. On failure the board is left as it was.
+    pub fn solve_in_place(&mut self, board: &mut Board) -> Result<SolveStats, SudokuError> {
+        let result = self.solve(board);
+        *board = result.solution?;
+
+        Ok(result.stats)
+    }
+
+    /// Counts the solutions of a board, stopping once `limit` have been found,
+    /// by ruling out each model found and solving again. Invalid boards have
+    /// no solutions and the node limit doesn't apply.
+    pub fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        let mut cdcl = match Cnf::encode(board) {
+            Ok(cnf) => Cdcl::load(&cnf),
+            Err(_) => return 0,
+        };
+
+        let mut count = 0;
+        while count < limit {
+            match cdcl.solve(None) {
+                Ok(model) => cdcl.block(&model),
+                Err(_) => break,
+            }
+            count += 1;
+        }
+
+        count
+    }
+}
+
+// a literal is a variable numbered from 0, doubled, plus one when negated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Lit(usize);
+
+impl Lit {
+    fn from_dimacs(literal: i32) -> Lit {
+        Lit((literal.unsigned_abs() as usize - 1) * 2 + (literal < 0) as usize)
+    }
+
+    fn var(self) -> usize {
+        self.0 >> 1
+    }
+
+    fn negated(self) -> bool {
+        self.0 & 1 == 1
+    }
+}
+
+impl std::ops::Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(self.0 ^ 1)
+    }
+}
+
+const ACTIVITY_DECAY: f64 = 0.95;
+
+struct Cdcl {
+    // the first two literals of every clause longer than one are watched,
+    // and a clause that is a reason has the literal it implied first
+    clauses: Vec<Vec<Lit>>,
+    watches: Vec<Vec<usize>>,
+    assigns: Vec<Option<bool>>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    trail: Vec<Lit>,
+    // where each decision level starts on the trail
+    levels: Vec<usize>,
+    head: usize,
+    activity: Vec<f64>,
+    increment: f64,
+    phase: Vec<bool>,
+    seen: Vec<bool>,
+    // an empty clause or two clashing unit clauses were added
+    unsatisfiable: bool,
+    stats: SolveStats,
+}
+
+impl Cdcl {
+    fn new(variables: usize) -> Cdcl {
+        Cdcl {
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); variables * 2],
+            assigns: vec![None; variables],
+            level: vec![0; variables],
+            reason: vec![None; variables],
+            trail: Vec::new(),
+            levels: Vec::new(),
+            head: 0,
+            activity: vec![0.0; variables],
+            increment: 1.0,
+            phase: vec![false; variables],
+            seen: vec![false; variables],
+            unsatisfiable: false,
+            stats: SolveStats::default(),
+        }
+    }
+
+    fn load(cnf: &Cnf) -> Cdcl {
+        let mut cdcl = Cdcl::new(cnf.variables());
+        for clause in cnf.clauses() {
+            cdcl.add_clause(clause.iter().map(|&literal| Lit::from_dimacs(literal)).collect());
+        }
+
+        cdcl
+    }
+
+    // adds a clause that rules out the model, leaving out the variables fixed
+    // before any decision since no other model can change those
+    fn block(&mut self, model: &[bool]) {
+        self.backjump(0);
+
+        let clause = (0..model.len())
+            .filter(|&var| model[var] && self.assigns[var].is_none())
+            .map(|var| !Lit(var * 2))
+            .collect();
+        self.add_clause(clause);
+    }
+
+    fn value(&self, lit: Lit) -> Option<bool> {
+        self.assigns[lit.var()].map(|value| value != lit.negated())
+    }
+
+    fn add_clause(&mut self, clause: Vec<Lit>) {
+        match clause.len() {
+            0 => self.unsatisfiable = true,
+            1 => match self.value(clause[0]) {
+                Some(true) => {}
+                Some(false) => self.unsatisfiable = true,
+                None => self.assign(clause[0], None),
+            },
+            _ => {
+                self.watch(&clause, self.clauses.len());
+                self.clauses.push(clause);
+            }
+        }
+    }
+
+    fn watch(&mut self, clause: &[Lit], index: usize) {
+        self.watches[clause[0].0].push(index);
+        self.watches[clause[1].0].push(index);
+    }
+
+    fn assign(&mut self, lit: Lit, reason: Option<usize>) {
+        let var = lit.var();
+        self.assigns[var] = Some(!lit.negated());
+        self.level[var] = self.levels.len();
+        self.reason[var] = reason;
+        self.trail.push(lit);
+    }
+
+    // the value of every variable in a satisfying assignment
+    fn solve(&mut self, node_limit: Option<u64>) -> Result<Vec<bool>, SudokuError> {
+        if self.unsatisfiable {
+            return Err(SudokuError::Unsolvable);
+        }
+
+        loop {
+            if let Some(conflict) = self.propagate() {
+                self.stats.backtracks += 1;
+                if self.levels.is_empty() {
+                    return Err(SudokuError::Unsolvable);
+                }
+
+                let (learnt, level) = self.analyze(conflict);
+                self.backjump(level);
+
+                if learnt.len() == 1 {
+                    self.assign(learnt[0], None);
+                } else {
+                    let index = self.clauses.len();
+                    self.watch(&learnt, index);
+                    self.assign(learnt[0], Some(index));
+                    self.clauses.push(learnt);
+                }
+
+                self.increment /= ACTIVITY_DECAY;
+                continue;
+            }
+
+            let var = match self.pick() {
+                Some(var) => var,
+                None => return Ok(self.assigns.iter().map(|value| value == &Some(true)).collect()),
+            };
+
+            let nodes = self.stats.nodes;
+            if node_limit.is_some_and(|limit| nodes >= limit) {
+                return Err(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes });
+            }
+            self.stats.nodes += 1;
+            self.stats.guesses += 1;
+
+            self.levels.push(self.trail.len());
+            self.stats.max_depth = self.stats.max_depth.max(self.levels.len());
+            self.assign(Lit(var * 2 + !self.phase[var] as usize), None);
+        }
+    }
+
+    // the unassigned variable with the highest activity
+    fn pick(&self) -> Option<usize> {
+        let mut best = None;
+        let mut highest = -1.0;
+
+        for var in (0..self.assigns.len()).filter(|&var| self.assigns[var].is_none()) {
+            if self.activity[var] > highest {
+                best = Some(var);
+                highest = self.activity[var];
+            }
+        }
+
+        best
+    }
+
+    // assigns every implied literal, returning a clause left with no true or
+    // unassigned literals if there is one
+    fn propagate(&mut self) -> Option<usize> {
+        while self.head < self.trail.len() {
+            let falsified = !self.trail[self.head];
+            self.head += 1;
+
+            let mut watching = std::mem::take(&mut self.watches[falsified.0]);
+            let mut i = 0;
+
+            while i < watching.len() {
+                let index = watching[i];
+                if self.clauses[index][0] == falsified {
+                    self.clauses[index].swap(0, 1);
+                }
+
+                let first = self.clauses[index][0];
+                if self.value(first) == Some(true) {
+                    i += 1;
+                    continue;
+                }
+
+                let replacement = (2..self.clauses[index].len()).find(|&k| self.value(self.clauses[index][k]) != Some(false));
+                if let Some(k) = replacement {
+                    self.clauses[index].swap(1, k);
+                    self.watches[self.clauses[index][1].0].push(index);
+                    watching.swap_remove(i);
+                    continue;
+                }
+
+                if self.value(first) == Some(false) {
+                    self.watches[falsified.0] = watching;
+                    self.head = self.trail.len();
+                    return Some(index);
+                }
+
+                self.assign(first, Some(index));
+                i += 1;
+            }
+
+            self.watches[falsified.0] = watching;
+        }
+
+        None
+    }
+
+    // learns the first unique implication point clause from a conflict, with
+    // its asserting literal first, and the level to jump back to
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let current = self.levels.len();
+        let mut learnt = vec![Lit(0)];
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut implied = None;
+        let mut index = self.trail.len();
+
+        loop {
+            let skip = implied.is_some() as usize;
+            for k in skip..self.clauses[clause].len() {
+                let lit = self.clauses[clause][k];
+                let var = lit.var();
+
+                if self.seen[var] || self.level[var] == 0 {
+                    continue;
+                }
+
+                self.seen[var] = true;
+                self.bump(var);
+                if self.level[var] == current {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+
+            // the next literal on the trail involved in the conflict
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index].var()] {
+                    break;
+                }
+            }
+
+            let lit = self.trail[index];
+            self.seen[lit.var()] = false;
+            implied = Some(lit);
+            pending -= 1;
+
+            if pending == 0 {
+                break;
+            }
+            clause = self.reason[lit.var()].unwrap();
+        }
+
+        learnt[0] = !implied.unwrap();
+        for lit in &learnt[1..] {
+            self.seen[lit.var()] = false;
+        }
+
+        // watch the literal from the highest level after the asserting one
+        let mut level = 0;
+        if learnt.len() > 1 {
+            let highest = (1..learnt.len()).max_by_key(|&k| self.level[learnt[k].var()]).unwrap();
+            learnt.swap(1, highest);
+            level = self.level[learnt[1].var()];
+        }
+
+        (learnt, level)
+    }
+
+    fn bump(&mut self, var: usize) {
+        self.activity[var] += self.increment;
+
+        if self.activity[var] > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.increment *= 1e-100;
+        }
+    }
+
+    fn backjump(&mut self, level: usize) {
+        if self.levels.len() <= level {
+            return;
+        }
+
+        let start = self.levels[level];
+        for lit in self.trail.drain(start..) {
+            let var = lit.var();
+            self.phase[var] = !lit.negated();
+            self.assigns[var] = None;
+            self.reason[var] = None;
+        }
+
+        self.levels.truncate(level);
+        self.head = start;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dlx::DlxSolver;
+    use crate::fixtures::puzzle;
+    use crate::game::{find_empty, is_valid_board, solve_puzzle};
+
+    #[test]
+    fn test_encode() {
+        let mut board = Board::empty(Geometry::new(2, 2).unwrap());
+        board.set(1, 2, 3);
+        let cnf = Cnf::encode(&board).unwrap();
+
+        // 16 cells and 12 units by 4 digits, each one clause plus 6 pairs,
+        // and the given
+        assert_eq!(64, cnf.variables());
+        assert_eq!((16 + 12 * 4) * 7 + 1, cnf.clauses().len());
+        assert_eq!(vec!(cnf.variable(1, 2, 3)), cnf.clauses()[cnf.clauses().len() - 1]);
+        assert_eq!(27, cnf.variable(1, 2, 3));
+    }
+
+    #[test]
+    fn test_encode_invalid_board() {
+        let mut board = puzzle();
+        board.set(0, 0, 2);
+
+        assert!(matches!(Cnf::encode(&board), Err(SudokuError::DuplicateInUnit { .. })));
+    }
+
+    #[test]
+    fn test_dimacs() {
+        let mut board = Board::empty(Geometry::new(2, 2).unwrap());
+        board.set(0, 0, 1);
+        let dimacs = Cnf::encode(&board).unwrap().to_string();
+        let lines: Vec<&str> = dimacs.lines().collect();
+
+        assert_eq!("c sudoku 4 x 4", lines[0]);
+        assert_eq!("p cnf 64 449", lines[1]);
+        assert_eq!("1 2 3 4 0", lines[2]);
+        assert_eq!("-1 -2 0", lines[3]);
+        assert_eq!("1 0", lines[lines.len() - 1]);
+        assert_eq!(2 + 449, lines.len());
+    }
+
+    #[test]
+    fn test_read_model() {
+        let mut board = Board::empty(Geometry::new(2, 2).unwrap());
+        let cnf = Cnf::encode(&board).unwrap();
+        board.set(0, 0, 2);
+        board.set(3, 1, 4);
+
+        let model = format!("c a comment\ns SATISFIABLE\nv -1 {} -3\nv {} 0\n", cnf.variable(0, 0, 2), cnf.variable(3, 1, 4));
+        assert_eq!(Ok(board.clone()), cnf.read_model(&model));
+        assert_eq!(Ok(board), cnf.read_model("2 -5 56"));
+    }
+
+    #[test]
+    fn test_read_bad_model() {
+        let cnf = Cnf::encode(&Board::new()).unwrap();
+
+        assert_eq!(Err(SudokuError::Unsolvable), cnf.read_model("s UNSATISFIABLE\n"));
+        assert_eq!(Err(invalid("\"x\" is not a literal".to_string())), cnf.read_model("v 1 x 0"));
+        assert_eq!(Err(invalid("there is no variable 730".to_string())), cnf.read_model("v 730 0"));
+        assert_eq!(Err(invalid("(0, 0) is both 1 and 2".to_string())), cnf.read_model("v 1 2 0"));
+    }
+
+    #[test]
+    fn test_solve_matches_backtracker() {
+        let mut expected = puzzle();
+        solve_puzzle(&mut expected).unwrap();
+
+        let mut board = puzzle();
+        let stats = SatSolver::new().solve_in_place(&mut board).unwrap();
+        assert_eq!(expected, board);
+        assert_eq!(stats.nodes, stats.guesses);
+
+        let cnf = Cnf::encode(&puzzle()).unwrap();
+        let model: Vec<String> = expected
+            .cells()
+            .map(|((row, col), value)| cnf.variable(row, col, value).to_string())
+            .collect();
+        assert_eq!(Ok(expected), cnf.read_model(&model.join(" ")));
+    }
+
+    #[test]
+    fn test_solve_hard_puzzle() {
+        let board = Board::from_rows(&[
+            vec!(8, 0, 0, 0, 0, 0, 0, 0, 0,),
+            vec!(0, 0, 3, 6, 0, 0, 0, 0, 0,),
+            vec!(0, 7, 0, 0, 9, 0, 2, 0, 0,),
+            vec!(0, 5, 0, 0, 0, 7, 0, 0, 0,),
+            vec!(0, 0, 0, 0, 4, 5, 7, 0, 0,),
+            vec!(0, 0, 0, 1, 0, 0, 0, 3, 0,),
+            vec!(0, 0, 1, 0, 0, 0, 0, 6, 8,),
+            vec!(0, 0, 8, 5, 0, 0, 0, 1, 0,),
+            vec!(0, 9, 0, 0, 0, 0, 4, 0, 0,),
+        ]).unwrap();
+        let result = SatSolver::new().solve(&board);
+
+        assert_eq!(DlxSolver::new().solve(&board).solution, result.solution);
+        assert!(result.stats.backtracks > 0);
+    }
+
+    #[test]
+    fn test_solve_other_sizes() {
+        for &(box_rows, box_cols) in &[(2, 2), (2, 3), (4, 4)] {
+            let board = Board::empty(Geometry::new(box_rows, box_cols).unwrap());
+            let solution = SatSolver::new().solve(&board).solution.unwrap();
+
+            assert!(is_valid_board(&solution) && find_empty(&solution).is_none());
+        }
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let mut board = puzzle();
+        assert_eq!(1, SatSolver::new().count_solutions(&board, 10));
+
+        board.set(0, 0, 0);
+        board.set(0, 5, 0);
+        board.set(1, 5, 0);
+        assert_eq!(DlxSolver::new().count_solutions(&board, 100), SatSolver::new().count_solutions(&board, 100));
+
+        let four = Board::empty(Geometry::new(2, 2).unwrap());
+        assert_eq!(288, SatSolver::new().count_solutions(&four, 1000));
+        assert_eq!(5, SatSolver::new().count_solutions(&four, 5));
+        assert_eq!(0, SatSolver::new().count_solutions(&four, 0));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        // row 8 leaves only a 9 for (8, 8) but column 8 already has one
+        let mut board = Board::new();
+        for col in 0..8 {
+            board.set(8, col, col as u8 + 1);
+        }
+        board.set(0, 8, 9);
+
+        assert_eq!(Err(SudokuError::Unsolvable), SatSolver::new().solve(&board).solution);
+    }
+
+    #[test]
+    fn test_node_limit() {
+        assert_eq!(
+            Err(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes: 3 }),
+            SatSolver::new().node_limit(3).solve(&Board::new()).solution
+        );
+    }
+}
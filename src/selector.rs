@@ -0,0 +1,175 @@
+use crate::candidates::Candidates;
+use crate::rng::Rng;
+
+/// Chooses which empty cell the solver fills next.
+pub trait CellSelector {
+    /// Picks one of the `empty` cells, which is never an empty slice, and
+    /// returns its index in the slice.
+    fn select(&mut self, candidates: &Candidates, empty: &[(usize, usize)]) -> usize;
+}
+
+/// The first empty cell in row-major order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FirstEmpty;
+
+impl CellSelector for FirstEmpty {
+    fn select(&mut self, _: &Candidates, _: &[(usize, usize)]) -> usize {
+        // the solver keeps untouched empty cells in row-major order
+        0
+    }
+}
+
+/// The empty cell with the fewest legal digits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MinimumRemainingValues;
+
+impl CellSelector for MinimumRemainingValues {
+    fn select(&mut self, candidates: &Candidates, empty: &[(usize, usize)]) -> usize {
+        let mut best = 0;
+        let mut fewest = u32::MAX;
+
+        for (i, &(row, col)) in empty.iter().enumerate() {
+            let count = candidates.count(row, col);
+            if count < fewest {
+                best = i;
+                fewest = count;
+            }
+
+            // a cell with nothing left is a dead end, no need to look further
+            if count == 0 {
+                break;
+            }
+        }
+
+        best
+    }
+}
+
+/// The empty cell with the fewest legal digits, ties going to the cell with
+/// the most empty peers under the candidates' rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MrvDegree;
+
+impl CellSelector for MrvDegree {
+    fn select(&mut self, candidates: &Candidates, empty: &[(usize, usize)]) -> usize {
+        let board = candidates.board();
+        let rules = candidates.rules();
+        let mut best = 0;
+        let mut fewest = u32::MAX;
+        let mut degree = 0;
+
+        for (i, &(row, col)) in empty.iter().enumerate() {
+            let count = candidates.count(row, col);
+            if count == 0 {
+                return i;
+            }
+            if count > fewest {
+                continue;
+            }
+
+            let peers = rules.peers(row, col).iter().filter(|&&position| board[position] == 0).count();
+            if count < fewest || peers > degree {
+                best = i;
+                fewest = count;
+                degree = peers;
+            }
+        }
+
+        best
+    }
+}
+
+/// A random empty cell, the same seed always picks the same cells.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RandomCell {
+    rng: Rng,
+}
+
+impl RandomCell {
+    pub fn new(seed: u64) -> RandomCell {
+        RandomCell { rng: Rng::new(seed) }
+    }
+}
+
+impl CellSelector for RandomCell {
+    fn select(&mut self, _: &Candidates, empty: &[(usize, usize)]) -> usize {
+        self.rng.below(empty.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::board::Board;
+    use crate::constraints::Rules;
+    use crate::fixtures::easy_puzzle;
+
+    fn puzzle() -> Candidates {
+        Candidates::new(&easy_puzzle()).unwrap()
+    }
+
+    fn empty(candidates: &Candidates) -> Vec<(usize, usize)> {
+        candidates.iter().map(|(position, _)| position).collect()
+    }
+
+    #[test]
+    fn test_first_empty() {
+        let candidates = puzzle();
+
+        assert_eq!(0, FirstEmpty.select(&candidates, &empty(&candidates)));
+    }
+
+    #[test]
+    fn test_minimum_remaining_values() {
+        let mut candidates = puzzle();
+        candidates.place(0, 0, 1).unwrap();
+        let empty = empty(&candidates);
+        let fewest = empty.iter().map(|&(row, col)| candidates.count(row, col)).min().unwrap();
+
+        let (row, col) = empty[MinimumRemainingValues.select(&candidates, &empty)];
+        assert_eq!(1, fewest);
+        assert_eq!(fewest, candidates.count(row, col));
+        assert_eq!((0, 1), (row, col));
+    }
+
+    #[test]
+    fn test_mrv_degree_breaks_ties() {
+        let mut board = Board::new();
+        board.set(0, 3, 1);
+        board.set(1, 0, 1);
+        let candidates = Candidates::new(&board).unwrap();
+        let empty = empty(&candidates);
+
+        // every cell that can't hold a 1 ties on eight digits, but (0, 0) to
+        // (0, 2) see both givens and so have one less empty peer than (0, 4)
+        assert_eq!((0, 0), empty[MinimumRemainingValues.select(&candidates, &empty)]);
+        assert_eq!((0, 4), empty[MrvDegree.select(&candidates, &empty)]);
+    }
+
+    #[test]
+    fn test_mrv_degree_uses_rules() {
+        let board = Board::new();
+        let rules = Arc::new(Rules::diagonal(board.geometry()));
+        let candidates = Candidates::with_rules(&board, rules).unwrap();
+        let empty = empty(&candidates);
+
+        // the centre is on both diagonals, so it has the most peers
+        assert_eq!((4, 4), empty[MrvDegree.select(&candidates, &empty)]);
+    }
+
+    #[test]
+    fn test_random_cell_is_seeded() {
+        let candidates = puzzle();
+        let empty = empty(&candidates);
+        let picks = |seed| {
+            let mut selector = RandomCell::new(seed);
+            (0..10).map(|_| selector.select(&candidates, &empty)).collect::<Vec<usize>>()
+        };
+
+        assert_eq!(picks(3), picks(3));
+        assert_ne!(picks(3), picks(4));
+        assert!(picks(3).iter().all(|&i| i < empty.len()));
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::board::Board;
+use crate::candidates::{each_solution, Candidates};
+use crate::constraints::Rules;
+use crate::error::SudokuError;
+
+/// How many solutions a board has, as far as telling a proper puzzle apart
+/// from a broken one goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Solvability {
+    Unsolvable,
+    Unique,
+    Multiple,
+}
+
+/// A board being edited one given at a time. The candidates are kept up to
+/// date with every edit and up to two solutions are remembered, so most edits
+/// are answered from what is already known and the rest only search from the
+/// current candidates.
+///
+/// ```
+/// use sudoku_solver::{Board, Solvability, SolverSession};
+///
+/// let mut session = SolverSession::new(&Board::new()).unwrap();
+/// assert_eq!(Solvability::Multiple, session.solvability());
+///
+/// session.place(0, 0, 5).unwrap();
+/// assert_eq!(Solvability::Multiple, session.solvability());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SolverSession {
+    candidates: Candidates,
+    // distinct solutions of the current board, at most two
+    solutions: Vec<Board>,
+    // None until the board has been checked since the last edit that could
+    // change the answer
+    solvability: Option<Solvability>,
+}
+
+impl SolverSession {
+    /// Starts a session under the classic rules. Invalid boards are rejected.
+    pub fn new(board: &Board) -> Result<SolverSession, SudokuError> {
+        SolverSession::with_rules(board, Rules::classic(board.geometry()))
+    }
+
+    pub fn with_rules(board: &Board, rules: Rules) -> Result<SolverSession, SudokuError> {
+        Ok(SolverSession {
+            candidates: Candidates::with_rules(board, Arc::new(rules))?,
+            solutions: Vec::new(),
+            solvability: None,
+        })
+    }
+
+    pub fn board(&self) -> &Board {
+        self.candidates.board()
+    }
+
+    pub fn candidates(&self) -> &Candidates {
+        &self.candidates
+    }
+
+    /// Adds a given, which must be one of the cell's candidates so the board
+    /// stays valid.
+    pub fn place(&mut self, row: usize, col: usize, digit: u8) -> Result<(), SudokuError> {
+        self.candidates.place(row, col, digit)?;
+
+        // a new given can only rule solutions out
+        self.solutions.retain(|solution| solution[(row, col)] == digit);
+        self.solvability = match (self.solvability, self.solutions.len()) {
+            (Some(Solvability::Unsolvable), _) => Some(Solvability::Unsolvable),
+            (Some(Solvability::Unique), 0) => Some(Solvability::Unsolvable),
+            (Some(Solvability::Unique), _) => Some(Solvability::Unique),
+            (_, 2) => Some(Solvability::Multiple),
+            _ => None,
+        };
+
+        Ok(())
+    }
+
+    /// Takes a given back out, returning the digit that was there.
+    pub fn remove(&mut self, row: usize, col: usize) -> Option<u8> {
+        let digit = self.candidates.remove(row, col)?;
+
+        // every known solution still fits, but there may be new ones
+        if self.solvability != Some(Solvability::Multiple) {
+            self.solvability = None;
+        }
+
+        Some(digit)
+    }
+
+    /// Whether the board as it stands has no, one or several solutions.
+    pub fn solvability(&mut self) -> Solvability {
+        if let Some(solvability) = self.solvability {
+            return solvability;
+        }
+
+        if self.solutions.len() < 2 {
+            let mut candidates = self.candidates.clone();
+            let solutions = &mut self.solutions;
+            let stop = AtomicBool::new(false);
+
+            // the known solution turns up again, only new ones are kept
+            each_solution(&mut candidates, &stop, &mut |solution| {
+                if !solutions.contains(solution) {
+                    solutions.push(solution.clone());
+                }
+                if solutions.len() >= 2 {
+                    stop.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
+        let solvability = match self.solutions.len() {
+            0 => Solvability::Unsolvable,
+            1 => Solvability::Unique,
+            _ => Solvability::Multiple,
+        };
+        self.solvability = Some(solvability);
+
+        solvability
+    }
+
+    /// The solutions found so far, checking the board first if an edit has
+    /// changed it. At most two are kept.
+    pub fn solutions(&mut self) -> &[Board] {
+        self.solvability();
+
+        &self.solutions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::dlx::DlxSolver;
+    use crate::fixtures::{easy_puzzle, solution};
+
+    fn expected(board: &Board) -> Solvability {
+        match DlxSolver::new().count_solutions(board, 2) {
+            0 => Solvability::Unsolvable,
+            1 => Solvability::Unique,
+            _ => Solvability::Multiple,
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let mut session = SolverSession::new(&solution()).unwrap();
+        assert_eq!(Solvability::Unique, session.solvability());
+        assert_eq!(&[solution()], session.solutions());
+
+        let mut board = solution();
+        board.set(0, 1, 1);
+        assert!(matches!(SolverSession::new(&board), Err(SudokuError::DuplicateInUnit { .. })));
+    }
+
+    #[test]
+    fn test_edits_match_fresh_count() {
+        let mut session = SolverSession::new(&Board::new()).unwrap();
+        let solution = solution();
+
+        // fill the board in, then empty it again, checking after every edit
+        for ((row, col), value) in solution.cells() {
+            session.place(row, col, value).unwrap();
+            assert_eq!(expected(session.board()), session.solvability());
+        }
+        for ((row, col), value) in solution.cells() {
+            assert_eq!(Some(value), session.remove(row, col));
+            assert_eq!(expected(session.board()), session.solvability());
+        }
+
+        assert_eq!(&Board::new(), session.board());
+        assert_eq!(None, session.remove(0, 0));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let board = Board::from_rows(&[
+            vec!(1, 2, 0, 0,),
+            vec!(0, 0, 0, 0,),
+            vec!(0, 0, 0, 0,),
+            vec!(0, 0, 0, 0,),
+        ]).unwrap();
+        let mut session = SolverSession::new(&board).unwrap();
+        assert_eq!(Solvability::Multiple, session.solvability());
+
+        // row 0 needs its 3 at (0, 2) or (0, 3), and a 3 at (1, 2) shares box 1
+        // with both
+        session.place(1, 2, 3).unwrap();
+        assert_eq!(Solvability::Unsolvable, session.solvability());
+        assert!(session.solutions().is_empty());
+
+        session.place(3, 3, 1).unwrap();
+        assert_eq!(Solvability::Unsolvable, session.solvability());
+
+        session.remove(1, 2);
+        assert_eq!(Solvability::Multiple, session.solvability());
+    }
+
+    #[test]
+    fn test_known_solutions_kept() {
+        let mut session = SolverSession::new(&Board::empty(Geometry::new(2, 3).unwrap())).unwrap();
+        assert_eq!(Solvability::Multiple, session.solvability());
+        let known = session.solutions().to_vec();
+
+        // a given both known solutions share keeps the board ambiguous, and
+        // both are still the ones remembered
+        let ((row, col), digit) = known[0].cells().filter(|&(position, value)| known[1][position] == value).last().unwrap();
+        session.place(row, col, digit).unwrap();
+        assert_eq!(Solvability::Multiple, session.solvability());
+        assert_eq!(&known[..], session.solutions());
+
+        // taking it back out can't make the board any less ambiguous
+        session.remove(row, col);
+        assert_eq!(Solvability::Multiple, session.solvability());
+        assert_eq!(&known[..], session.solutions());
+    }
+
+    #[test]
+    fn test_unique_keeps_solution() {
+        let mut session = SolverSession::new(&easy_puzzle()).unwrap();
+        assert_eq!(Solvability::Unique, session.solvability());
+        assert_eq!(&[solution()], session.solutions());
+
+        // the solution's own digit keeps it the only one
+        session.place(0, 0, 1).unwrap();
+        assert_eq!(Solvability::Unique, session.solvability());
+        assert_eq!(&[solution()], session.solutions());
+
+        // 6 is still a candidate for (0, 5) but the solution has an 8 there
+        session.place(0, 5, 6).unwrap();
+        assert_eq!(Solvability::Unsolvable, session.solvability());
+        assert!(session.solutions().is_empty());
+    }
+
+    #[test]
+    fn test_place_illegal() {
+        let mut session = SolverSession::new(&Board::new()).unwrap();
+        session.place(0, 0, 5).unwrap();
+
+        assert_eq!(Err(SudokuError::IllegalMove { row: 0, col: 1, value: 5 }), session.place(0, 1, 5));
+        assert_eq!(Err(SudokuError::IllegalMove { row: 0, col: 0, value: 4 }), session.place(0, 0, 4));
+    }
+
+    #[test]
+    fn test_rules() {
+        let geometry = Geometry::new(2, 2).unwrap();
+        let mut session = SolverSession::with_rules(&Board::empty(geometry), Rules::diagonal(geometry)).unwrap();
+
+        assert_eq!(Solvability::Multiple, session.solvability());
+        assert!(session.solutions().iter().all(|solution| Rules::diagonal(geometry).is_valid_board(solution)));
+    }
+}
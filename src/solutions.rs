@@ -0,0 +1,96 @@
+use crate::board::Board;
+use crate::engine::SearchEngine;
+
+/// Lazily enumerates every solution of a board in the same order the
+/// backtracking solver would find them. Invalid boards have no solutions.
+pub fn solutions(board: &Board) -> Solutions {
+    Solutions {
+        engine: SearchEngine::new(board).ok(),
+    }
+}
+
+/// A backtracking search that can stop after each solution and pick up where
+/// it left off.
+#[derive(Clone, Debug)]
+pub struct Solutions {
+    engine: Option<SearchEngine>,
+}
+
+impl Solutions {
+    /// The search underneath, for saving it or stepping it by hand. None for
+    /// an invalid board.
+    pub fn engine(&self) -> Option<&SearchEngine> {
+        self.engine.as_ref()
+    }
+}
+
+impl Iterator for Solutions {
+    type Item = Board;
+
+    fn next(&mut self) -> Option<Board> {
+        self.engine.as_mut()?.next_solution()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::two_solutions;
+    use crate::game::{count_solutions, find_empty, is_valid_board, solve_puzzle};
+
+    #[test]
+    fn test_solutions_in_solver_order() {
+        let board = two_solutions();
+        let found: Vec<Board> = solutions(&board).collect();
+
+        assert_eq!(2, found.len());
+        assert_eq!(count_solutions(&board, 10), found.len());
+        assert_ne!(found[0], found[1]);
+
+        let mut solved = board.clone();
+        assert_eq!(&found[0], solve_puzzle(&mut solved).unwrap());
+        assert_eq!((5, 8), (found[0].get(0, 2), found[1].get(0, 2)));
+    }
+
+    #[test]
+    fn test_solutions_are_lazy() {
+        let found: Vec<Board> = solutions(&Board::new()).take(3).collect();
+
+        assert_eq!(3, found.len());
+        assert_ne!(found[1], found[2]);
+        assert!(found.iter().all(|board| is_valid_board(board) && find_empty(board).is_none()));
+    }
+
+    #[test]
+    fn test_solutions_of_solved_board() {
+        let mut board = two_solutions();
+        board.set(0, 2, 5);
+        board.set(0, 5, 8);
+        board.set(1, 2, 8);
+        board.set(1, 5, 5);
+
+        let mut found = solutions(&board);
+        assert_eq!(Some(board), found.next());
+        assert_eq!(None, found.next());
+        assert_eq!(None, found.next());
+    }
+
+    #[test]
+    fn test_solutions_of_invalid_board() {
+        let mut board = two_solutions();
+        board.set(0, 2, 1);
+
+        assert_eq!(None, solutions(&board).next());
+        assert!(solutions(&board).engine().is_none());
+    }
+
+    #[test]
+    fn test_solutions_resume_from_snapshot() {
+        let mut found = solutions(&two_solutions());
+        found.next().unwrap();
+
+        let snapshot = found.engine().unwrap().to_bytes();
+        let mut resumed = SearchEngine::from_bytes(&snapshot).unwrap();
+        assert_eq!(found.next(), resumed.next_solution());
+    }
+}
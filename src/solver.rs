@@ -0,0 +1,878 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::candidates::{digit_bit, digits, each_solution, Candidates};
+use crate::constraints::Rules;
+use crate::error::SudokuError;
+use crate::observer::SolveObserver;
+use crate::order::{Ascending, ValueOrder};
+use crate::render::{Classic, Renderer};
+use crate::selector::{CellSelector, FirstEmpty};
+
+// how often the progress sink hears about the search
+const PROGRESS_INTERVAL: u64 = 100_000;
+
+// how often the clock and cancellation token are checked, in nodes
+const CHECK_INTERVAL: u64 = 64;
+
+/// How hard the search had to work, a crude measure of how difficult a
+/// puzzle is.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SolveStats {
+    /// Calls into the search, one per partial board looked at.
+    pub nodes: u64,
+    /// Digits the search tried in a cell.
+    pub placements: u64,
+    /// Digits filled in by propagation, these aren't counted as placements.
+    pub propagated: u64,
+    /// Tried digits taken back out after their branch failed.
+    pub backtracks: u64,
+    /// The most digits the search had placed at once, not counting the ones
+    /// propagation filled in.
+    pub max_depth: usize,
+    /// Placements in cells that had more than one legal digit.
+    pub guesses: u64,
+    pub elapsed: Duration,
+}
+
+/// The outcome of a solve along with what it took to get there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SolveResult {
+    pub solution: Result<Board, SudokuError>,
+    pub stats: SolveStats,
+}
+
+/// The limit that stopped a search early.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Budget {
+    Nodes,
+    Time,
+}
+
+/// A flag that stops any solve holding a clone of it. Tokens can be shared
+/// across threads.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A configurable backtracking solver.
+///
+/// ```
+/// use sudoku_solver::{Board, MinimumRemainingValues, Solver};
+///
+/// let solved = Solver::new()
+///     .cell_selector(MinimumRemainingValues)
+///     .node_limit(10_000)
+///     .solve(&Board::new())
+///     .solution
+///     .unwrap();
+/// ```
+pub struct Solver<'a> {
+    selector: Box<dyn CellSelector + 'a>,
+    order: Box<dyn ValueOrder + 'a>,
+    node_limit: Option<u64>,
+    time_limit: Option<Duration>,
+    cancel: Option<CancelToken>,
+    progress: Option<Box<dyn Write + 'a>>,
+    observer: Option<Box<dyn SolveObserver + 'a>>,
+    propagation: bool,
+    rules: Option<Arc<Rules>>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new() -> Solver<'a> {
+        Solver {
+            selector: Box::new(FirstEmpty),
+            order: Box::new(Ascending),
+            node_limit: None,
+            time_limit: None,
+            cancel: None,
+            progress: None,
+            observer: None,
+            propagation: false,
+            rules: None,
+        }
+    }
+
+    pub fn cell_selector<S: CellSelector + 'a>(mut self, selector: S) -> Solver<'a> {
+        self.selector = Box::new(selector);
+        self
+    }
+
+    pub fn value_order<O: ValueOrder + 'a>(mut self, order: O) -> Solver<'a> {
+        self.order = Box::new(order);
+        self
+    }
+
+    /// Fills in naked and hidden singles before the search and after every
+    /// guess. Off by default.
+    pub fn propagation(mut self, propagation: bool) -> Solver<'a> {
+        self.propagation = propagation;
+        self
+    }
+
+    /// Solves under these rules instead of the classic rows, columns and
+    /// boxes. Boards of another geometry are rejected.
+    pub fn rules(mut self, rules: Rules) -> Solver<'a> {
+        self.rules = Some(Arc::new(rules));
+        self
+    }
+
+    /// Gives up once the search has visited this many nodes.
+    pub fn node_limit(mut self, limit: u64) -> Solver<'a> {
+        self.node_limit = Some(limit);
+        self
+    }
+
+    /// Gives up once `limit` has passed since the solve started.
+    pub fn time_limit(mut self, limit: Duration) -> Solver<'a> {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Stops the search as soon as it notices `token` has been cancelled.
+    pub fn cancel_token(mut self, token: CancelToken) -> Solver<'a> {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Reports how the search is going, and the solution once found, to `sink`.
+    pub fn progress<W: Write + 'a>(mut self, sink: W) -> Solver<'a> {
+        self.progress = Some(Box::new(sink));
+        self
+    }
+
+    /// Tells `observer` about every placement, undo, branch and solution.
+    /// Pass `&mut observer` to read it again after the solve.
+    pub fn observer<O: SolveObserver + 'a>(mut self, observer: O) -> Solver<'a> {
+        self.observer = Some(Box::new(observer));
+        self
+    }
+
+    /// Solves a copy of the board, leaving the original untouched.
+    pub fn solve(&mut self, board: &Board) -> SolveResult {
+        let (solution, stats) = self.run(board);
+
+        SolveResult { solution, stats }
+    }
+
+    /// Solves the board in place. On failure the board is left as it was.
+    pub fn solve_in_place(&mut self, board: &mut Board) -> Result<SolveStats, SudokuError> {
+        let (solution, stats) = self.run(board);
+        *board = solution?;
+
+        Ok(stats)
+    }
+
+    /// Counts the solutions of a board under the solver's rules, stopping
+    /// once `limit` have been found. Invalid boards have no solutions. Only
+    /// the rules apply, not the selector, order, limits or observer.
+    pub fn count_solutions(&mut self, board: &Board, limit: usize) -> usize {
+        let mut candidates = match self.candidates(board) {
+            Ok(candidates) => candidates,
+            Err(_) => return 0,
+        };
+
+        let mut count = 0;
+        let stop = AtomicBool::new(limit == 0);
+        each_solution(&mut candidates, &stop, &mut |_| {
+            count += 1;
+            if count >= limit {
+                stop.store(true, Ordering::Relaxed);
+            }
+        });
+
+        count
+    }
+
+    /// Whether a board keeps the solver's rules, the classic ones unless
+    /// others were given.
+    pub fn is_valid_board(&self, board: &Board) -> bool {
+        match &self.rules {
+            Some(rules) => rules.is_valid_board(board),
+            None => Rules::classic(board.geometry()).is_valid_board(board),
+        }
+    }
+
+    fn candidates(&self, board: &Board) -> Result<Candidates, SudokuError> {
+        let rules = match &self.rules {
+            Some(rules) => rules.clone(),
+            None => Arc::new(Rules::classic(board.geometry())),
+        };
+
+        Candidates::with_rules(board, rules)
+    }
+
+    fn run(&mut self, board: &Board) -> (Result<Board, SudokuError>, SolveStats) {
+        let candidates = match self.candidates(board) {
+            Ok(candidates) => candidates,
+            Err(error) => return (Err(error), SolveStats::default()),
+        };
+
+        // a search of its own for when nobody is watching, where every
+        // observer call compiles away
+        let mut observer = self.observer.take();
+        let result = match observer.as_mut() {
+            Some(observer) => self.search(board, candidates, &mut **observer),
+            None => self.search(board, candidates, ()),
+        };
+        self.observer = observer;
+
+        result
+    }
+
+    fn search<O: SolveObserver>(
+        &mut self,
+        board: &Board,
+        mut candidates: Candidates,
+        observer: O,
+    ) -> (Result<Board, SudokuError>, SolveStats) {
+        let start = Instant::now();
+        // only a unit covering every digit must hold each of them somewhere,
+        // so the smaller ones can't give hidden singles
+        let units = if self.propagation {
+            candidates
+                .rules()
+                .units()
+                .filter(|(_, cells)| cells.len() == board.size())
+                .map(|(_, cells)| cells.to_vec())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut search = Search {
+            selector: &mut *self.selector,
+            order: &mut *self.order,
+            node_limit: self.node_limit,
+            deadline: self.time_limit.map(|limit| start + limit),
+            cancel: self.cancel.as_ref(),
+            progress: self.progress.as_mut().map(|sink| &mut **sink as &mut dyn Write),
+            propagation: self.propagation,
+            units,
+            empty: board.cells().filter(|&(_, value)| value == 0).map(|(position, _)| position).collect(),
+            filled: 0,
+            trail: Vec::new(),
+            digits: Vec::new(),
+            observer,
+            stats: SolveStats::default(),
+        };
+
+        let outcome = search.get_solution(&mut candidates, 0);
+        search.stats.elapsed = start.elapsed();
+        let nodes = search.stats.nodes;
+
+        // progress output is best effort, a broken sink never fails the solve
+        let result = match outcome {
+            Outcome::Solved => {
+                search.report(|out| Classic::default().render(candidates.board(), out));
+                Ok(candidates.board().clone())
+            }
+            Outcome::Exhausted => {
+                search.report(|out| writeln!(out, "unsolvable after {} nodes", nodes));
+                Err(SudokuError::Unsolvable)
+            }
+            Outcome::Stopped(error) => {
+                search.report(|out| writeln!(out, "stopped after {} nodes: {}", nodes, error));
+                Err(error)
+            }
+        };
+
+        (result, search.stats)
+    }
+}
+
+impl<'a> Default for Solver<'a> {
+    fn default() -> Solver<'a> {
+        Solver::new()
+    }
+}
+
+enum Outcome {
+    Solved,
+    Exhausted,
+    // a budget ran out or the solve was cancelled
+    Stopped(SudokuError),
+}
+
+struct Search<'s, O> {
+    selector: &'s mut dyn CellSelector,
+    order: &'s mut dyn ValueOrder,
+    node_limit: Option<u64>,
+    deadline: Option<Instant>,
+    cancel: Option<&'s CancelToken>,
+    progress: Option<&'s mut dyn Write>,
+    propagation: bool,
+    // the cells of every row, column and box, only needed for propagation
+    units: Vec<Vec<(usize, usize)>>,
+    // the cells that started out empty, the first `filled` of them have a
+    // digit and the rest are still empty in row-major order
+    empty: Vec<(usize, usize)>,
+    filled: usize,
+    // where in `empty` each filled cell was when it was filled, newest last
+    trail: Vec<usize>,
+    // one buffer per depth for the digits to try, kept between nodes to save
+    // allocating
+    digits: Vec<Vec<u8>>,
+    observer: O,
+    stats: SolveStats,
+}
+
+impl<'s, O: SolveObserver> Search<'s, O> {
+    fn get_solution(&mut self, candidates: &mut Candidates, depth: usize) -> Outcome {
+        if let Some(error) = self.check_limits() {
+            return Outcome::Stopped(error);
+        }
+
+        self.stats.nodes += 1;
+        if self.stats.nodes % PROGRESS_INTERVAL == 0 {
+            let nodes = self.stats.nodes;
+            self.report(|out| writeln!(out, "visited {} nodes", nodes));
+        }
+
+        let mark = self.trail.len();
+        if self.propagation && !self.propagate(candidates) {
+            self.undo_to(candidates, mark);
+            return Outcome::Exhausted;
+        }
+
+        self.stats.max_depth = self.stats.max_depth.max(depth);
+        if self.filled == self.empty.len() {
+            self.observer.on_solution(candidates.board());
+            return Outcome::Solved;
+        }
+
+        let chosen = self.filled + self.selector.select(candidates, &self.empty[self.filled..]);
+        let (row, col) = self.empty[chosen];
+
+        if self.digits.len() == depth {
+            self.digits.push(Vec::new());
+        }
+        let mut digits = std::mem::take(&mut self.digits[depth]);
+        digits.clear();
+        self.order.order(candidates, (row, col), candidates.get(row, col), &mut digits);
+        self.observer.on_branch(row, col, &digits);
+
+        let outcome = self.try_digits(candidates, depth, chosen, &digits);
+        self.digits[depth] = digits;
+
+        if let Outcome::Solved = outcome {
+            return outcome;
+        }
+
+        self.undo_to(candidates, mark);
+
+        outcome
+    }
+
+    fn try_digits(&mut self, candidates: &mut Candidates, depth: usize, chosen: usize, digits: &[u8]) -> Outcome {
+        let guessing = digits.len() > 1;
+
+        for &digit in digits {
+            self.fill(candidates, chosen, digit, false);
+            self.stats.placements += 1;
+            if guessing {
+                self.stats.guesses += 1;
+            }
+
+            match self.get_solution(candidates, depth + 1) {
+                Outcome::Exhausted => {}
+                outcome => return outcome,
+            }
+
+            self.undo(candidates);
+            self.stats.backtracks += 1;
+        }
+
+        Outcome::Exhausted
+    }
+
+    // fills naked and hidden singles until there are none left, false if the
+    // board turns out to have a cell or a unit with no digit left
+    fn propagate(&mut self, candidates: &mut Candidates) -> bool {
+        let all = candidates.board().geometry().all_digits();
+        let mut changed = true;
+
+        while changed {
+            changed = false;
+
+            // naked singles, filling a cell only moves cells already looked at
+            for i in self.filled..self.empty.len() {
+                let (row, col) = self.empty[i];
+                let mask = candidates.get(row, col);
+
+                if mask == 0 {
+                    return false;
+                }
+                if mask.is_power_of_two() {
+                    self.fill(candidates, i, mask.trailing_zeros() as u8 + 1, true);
+                    self.stats.propagated += 1;
+                    changed = true;
+                }
+            }
+
+            // hidden singles
+            for u in 0..self.units.len() {
+                let mut placed = 0;
+                let mut once = 0;
+                let mut twice = 0;
+
+                for &(row, col) in &self.units[u] {
+                    let value = candidates.board()[(row, col)];
+                    if value != 0 {
+                        placed |= digit_bit(value);
+                    } else {
+                        let mask = candidates.get(row, col);
+                        twice |= once & mask;
+                        once |= mask;
+                    }
+                }
+
+                if all & !(placed | once) != 0 {
+                    return false;
+                }
+
+                for digit in digits(once & !twice) {
+                    let position = self.units[u].iter().copied().find(|&(row, col)| candidates.contains(row, col, digit));
+
+                    // an earlier single may have taken the only cell, the
+                    // next pass finds the unit has no room for this digit
+                    if let Some(position) = position {
+                        let i = self.filled + self.empty[self.filled..].iter().position(|&cell| cell == position).unwrap();
+                        self.fill(candidates, i, digit, true);
+                        self.stats.propagated += 1;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    // fills `empty[i]`, moving it to the end of the filled cells
+    fn fill(&mut self, candidates: &mut Candidates, i: usize, digit: u8, forced: bool) {
+        let (row, col) = self.empty[i];
+        candidates.fill(row, col, digit);
+        self.observer.on_place(row, col, digit, forced);
+
+        self.empty[self.filled..=i].rotate_right(1);
+        self.filled += 1;
+        self.trail.push(i);
+    }
+
+    // takes out the most recently filled digit, putting its cell back where it was
+    fn undo(&mut self, candidates: &mut Candidates) {
+        let i = self.trail.pop().unwrap();
+        self.filled -= 1;
+
+        let (row, col) = self.empty[self.filled];
+        if let Some(digit) = candidates.remove(row, col) {
+            self.observer.on_undo(row, col, digit);
+        }
+        self.empty[self.filled..=i].rotate_left(1);
+    }
+
+    fn undo_to(&mut self, candidates: &mut Candidates, mark: usize) {
+        while self.trail.len() > mark {
+            self.undo(candidates);
+        }
+    }
+
+    fn check_limits(&self) -> Option<SudokuError> {
+        let nodes = self.stats.nodes;
+
+        if self.node_limit.is_some_and(|limit| nodes >= limit) {
+            return Some(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes });
+        }
+
+        // the clock and token are only looked at every so often to keep them
+        // out of the hot path
+        if nodes % CHECK_INTERVAL != 0 {
+            return None;
+        }
+
+        if self.cancel.is_some_and(|token| token.is_cancelled()) {
+            return Some(SudokuError::Cancelled { nodes });
+        }
+
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Some(SudokuError::BudgetExhausted { budget: Budget::Time, nodes });
+        }
+
+        None
+    }
+
+    fn report<F: FnOnce(&mut dyn Write) -> std::io::Result<()>>(&mut self, write: F) {
+        if let Some(out) = self.progress.as_mut() {
+            let _ = write(&mut **out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::Geometry;
+    use crate::constraints::AntiKnight;
+    use crate::fixtures::{easy_puzzle, solution, two_solutions};
+    use crate::observer::SolveObserver;
+    use crate::game::{find_empty, is_valid_board};
+    use crate::order::{Descending, LeastConstrainingValue, RandomOrder};
+    use crate::selector::{MinimumRemainingValues, MrvDegree, RandomCell};
+
+    macro_rules! test_solver_options {
+        ($($name:ident: $value:expr,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let (board, solution) = (easy_puzzle(), solution());
+
+                    for &propagation in &[false, true] {
+                        let (selector, order) = $value;
+                        let mut solver = Solver::new().cell_selector(selector).value_order(order).propagation(propagation);
+
+                        assert_eq!(Ok(solution.clone()), solver.solve(&board).solution);
+                    }
+                }
+            )*
+        }
+    }
+
+    test_solver_options! {
+        first_empty_ascending: (FirstEmpty, Ascending),
+        first_empty_descending: (FirstEmpty, Descending),
+        mrv_ascending: (MinimumRemainingValues, Ascending),
+        mrv_descending: (MinimumRemainingValues, Descending),
+        mrv_degree_ascending: (MrvDegree, Ascending),
+        random_ascending: (RandomCell::new(1), Ascending),
+        random_descending: (RandomCell::new(2), Descending),
+        mrv_least_constraining: (MinimumRemainingValues, LeastConstrainingValue),
+        first_empty_random: (FirstEmpty, RandomOrder::new(3)),
+    }
+
+    #[test]
+    fn test_solve_leaves_board_untouched() {
+        let (board, solution) = (easy_puzzle(), solution());
+        let original = board.clone();
+
+        assert_eq!(Ok(solution.clone()), Solver::new().solve(&board).solution);
+        assert_eq!(original, board);
+
+        let mut in_place = board.clone();
+        Solver::new().solve_in_place(&mut in_place).unwrap();
+        assert_eq!(solution, in_place);
+    }
+
+    #[test]
+    fn test_value_order_picks_solution() {
+        let board = two_solutions();
+        let ascending = Solver::new().solve(&board).solution.unwrap();
+        let descending = Solver::new().value_order(Descending).solve(&board).solution.unwrap();
+
+        assert_eq!(5, ascending.get(0, 2));
+        assert_eq!(8, descending.get(0, 2));
+    }
+
+    #[test]
+    fn test_random_order_varies_grids() {
+        let grid = |seed| Solver::new().value_order(RandomOrder::new(seed)).solve(&Board::new()).solution.unwrap();
+
+        assert_eq!(grid(1), grid(1));
+        assert_ne!(grid(1), grid(2));
+        assert_ne!(Solver::new().solve(&Board::new()).solution.unwrap(), grid(1));
+        assert!(is_valid_board(&grid(2)) && find_empty(&grid(2)).is_none());
+    }
+
+    // valid but unsolvable, (8, 8) has no digit left but the first empty cell
+    // search only finds out after filling in the rest of the board
+    fn hopeless() -> Board {
+        let mut board = Board::new();
+        for col in 0..8 {
+            board.set(8, col, col as u8 + 1);
+        }
+        board.set(0, 8, 9);
+
+        board
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let mut board = easy_puzzle();
+        let original = board.clone();
+
+        assert_eq!(
+            Err(SudokuError::BudgetExhausted { budget: Budget::Nodes, nodes: 10 }),
+            Solver::new().node_limit(10).solve_in_place(&mut board)
+        );
+        assert_eq!(original, board);
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let mut board = hopeless();
+        let result = Solver::new().time_limit(Duration::from_millis(20)).solve_in_place(&mut board);
+
+        match result {
+            Err(SudokuError::BudgetExhausted { budget: Budget::Time, nodes }) => assert!(nodes > 0),
+            other => panic!("expected the time budget to run out, got {:?}", other),
+        }
+        assert_eq!(hopeless(), board);
+    }
+
+    #[test]
+    fn test_cancelled_before_start() {
+        let token = CancelToken::new();
+        token.cancel();
+
+        assert_eq!(
+            Err(SudokuError::Cancelled { nodes: 0 }),
+            Solver::new().cancel_token(token).solve(&hopeless()).solution
+        );
+    }
+
+    #[test]
+    fn test_cancelled_from_another_thread() {
+        let token = CancelToken::new();
+        let canceller = token.clone();
+
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            canceller.cancel();
+        });
+
+        let result = Solver::new().cancel_token(token.clone()).solve(&hopeless()).solution;
+        handle.join().unwrap();
+
+        assert!(token.is_cancelled());
+        match result {
+            Err(SudokuError::Cancelled { nodes }) => assert!(nodes > 0),
+            other => panic!("expected the solve to be cancelled, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let mut board = easy_puzzle();
+        board.set(8, 8, 3);
+
+        assert_eq!(Err(SudokuError::Unsolvable), Solver::new().solve(&board).solution);
+    }
+
+    #[test]
+    fn test_mrv_finds_dead_end() {
+        let result = Solver::new()
+            .cell_selector(MinimumRemainingValues)
+            .solve(&hopeless());
+
+        assert_eq!(Err(SudokuError::Unsolvable), result.solution);
+        assert_eq!(1, result.stats.nodes);
+    }
+
+    #[test]
+    fn test_rules() {
+        let geometry = Geometry::new(2, 2).unwrap();
+        let board = Board::empty(geometry);
+        let classic = Solver::new().solve(&board).solution.unwrap();
+
+        assert!(!Rules::diagonal(geometry).is_valid_board(&classic));
+        for &propagation in &[false, true] {
+            let solved = Solver::new().rules(Rules::diagonal(geometry)).propagation(propagation).solve(&board);
+            assert!(Rules::diagonal(geometry).is_valid_board(&solved.solution.unwrap()));
+        }
+
+        let rules = Rules::classic(Geometry::classic()).constraint(AntiKnight);
+        let solved = Solver::new()
+            .rules(rules)
+            .cell_selector(MinimumRemainingValues)
+            .propagation(true)
+            .solve(&Board::new())
+            .solution
+            .unwrap();
+        assert!(Rules::classic(Geometry::classic()).constraint(AntiKnight).is_valid_board(&solved));
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let geometry = Geometry::new(2, 2).unwrap();
+        let board = Board::empty(geometry);
+
+        assert_eq!(288, Solver::new().count_solutions(&board, 1000));
+        assert_eq!(10, Solver::new().count_solutions(&board, 10));
+        assert_eq!(0, Solver::new().count_solutions(&board, 0));
+        assert_eq!(2, Solver::new().count_solutions(&two_solutions(), 10));
+        assert_eq!(0, Solver::new().count_solutions(&hopeless(), 10));
+
+        let diagonal = Solver::new().rules(Rules::diagonal(geometry)).count_solutions(&board, 1000);
+        assert!(0 < diagonal && diagonal < 288);
+    }
+
+    #[test]
+    fn test_rules_wrong_geometry() {
+        let rules = Rules::classic(Geometry::new(2, 2).unwrap());
+
+        assert_eq!(Err(SudokuError::WrongDimensions { rows: 9, cols: 9 }), Solver::new().rules(rules).solve(&Board::new()).solution);
+    }
+
+    #[test]
+    fn test_propagation_solves_without_guessing() {
+        let (board, solution) = (easy_puzzle(), solution());
+        let result = Solver::new().propagation(true).solve(&board);
+        let empty = board.cells().filter(|&(_, value)| value == 0).count();
+
+        assert_eq!(Ok(solution), result.solution);
+        assert_eq!((1, 0, 0), (result.stats.nodes, result.stats.placements, result.stats.guesses));
+        assert_eq!(empty as u64, result.stats.propagated);
+        assert_eq!(0, result.stats.max_depth);
+    }
+
+    #[test]
+    fn test_propagation_finds_same_solution() {
+        for board in &[Board::new(), Board::empty(Geometry::new(2, 3).unwrap())] {
+            let plain = Solver::new().solve(board);
+            let propagated = Solver::new().propagation(true).solve(board);
+
+            assert_eq!(plain.solution, propagated.solution);
+            assert!(propagated.stats.nodes < plain.stats.nodes);
+            assert!(propagated.stats.max_depth < plain.stats.max_depth);
+            assert!(propagated.stats.max_depth as u64 <= propagated.stats.placements);
+        }
+
+        let descending = Solver::new().propagation(true).value_order(Descending).solve(&two_solutions());
+        assert_eq!(8, descending.solution.unwrap().get(0, 2));
+    }
+
+    #[test]
+    fn test_propagation_finds_dead_end() {
+        let result = Solver::new().propagation(true).solve(&hopeless());
+
+        assert_eq!(Err(SudokuError::Unsolvable), result.solution);
+        assert_eq!(1, result.stats.nodes);
+
+        let mut board = easy_puzzle();
+        board.set(8, 8, 3);
+        assert_eq!(Err(SudokuError::Unsolvable), Solver::new().propagation(true).solve(&board).solution);
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        branches: u64,
+        guesses: u64,
+        forced: u64,
+        undos: u64,
+        solutions: Vec<Board>,
+    }
+
+    impl SolveObserver for Counter {
+        fn on_branch(&mut self, _: usize, _: usize, _: &[u8]) {
+            self.branches += 1;
+        }
+
+        fn on_place(&mut self, _: usize, _: usize, _: u8, forced: bool) {
+            if forced {
+                self.forced += 1;
+            } else {
+                self.guesses += 1;
+            }
+        }
+
+        fn on_undo(&mut self, _: usize, _: usize, _: u8) {
+            self.undos += 1;
+        }
+
+        fn on_solution(&mut self, board: &Board) {
+            self.solutions.push(board.clone());
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut counter = Counter::default();
+        let board = Board::new();
+        let result = Solver::new().observer(&mut counter).solve(&board);
+        let stats = result.stats;
+
+        assert_eq!(vec!(result.solution.unwrap()), counter.solutions);
+        assert_eq!(stats.nodes - 1, counter.branches);
+        assert_eq!((stats.placements, 0, stats.backtracks), (counter.guesses, counter.forced, counter.undos));
+    }
+
+    #[test]
+    fn test_observer_with_propagation() {
+        let mut counter = Counter::default();
+        let mut board = easy_puzzle();
+        board.set(8, 8, 3);
+        let stats = Solver::new().propagation(true).observer(&mut counter).solve(&board).stats;
+
+        assert!(counter.solutions.is_empty());
+        assert!(counter.forced > 0);
+        assert_eq!((stats.placements, stats.propagated), (counter.guesses, counter.forced));
+        assert_eq!(counter.guesses + counter.forced, counter.undos);
+    }
+
+    #[test]
+    fn test_progress() {
+        let (board, solution) = (easy_puzzle(), solution());
+        let mut out = Vec::new();
+
+        Solver::new().progress(&mut out).solve(&board).solution.unwrap();
+
+        let mut expected = Vec::new();
+        Classic::default().render(&solution, &mut expected).unwrap();
+        assert_eq!(String::from_utf8(expected).unwrap(), String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn test_stats_single_guess() {
+        let result = Solver::new().solve(&two_solutions());
+        let stats = result.stats;
+
+        assert!(result.solution.is_ok());
+        assert_eq!(
+            (5, 4, 0, 4, 1),
+            (stats.nodes, stats.placements, stats.backtracks, stats.max_depth, stats.guesses)
+        );
+    }
+
+    #[test]
+    fn test_stats_add_up() {
+        let mut board = easy_puzzle();
+        let empty = board.cells().filter(|&(_, value)| value == 0).count();
+        let stats = Solver::new().solve_in_place(&mut board).unwrap();
+
+        assert_eq!(stats.nodes, stats.placements + 1);
+        assert_eq!(stats.placements, stats.backtracks + empty as u64);
+        assert_eq!(empty, stats.max_depth);
+        assert!(stats.guesses <= stats.placements);
+    }
+
+    #[test]
+    fn test_stats_on_failure() {
+        let result = Solver::new().node_limit(100).solve(&hopeless());
+
+        assert_eq!(100, result.stats.nodes);
+        assert!(result.stats.backtracks > 0);
+
+        let mut board = hopeless();
+        board.set(0, 0, 9);
+        assert_eq!(SolveStats::default(), Solver::new().solve(&board).stats);
+    }
+}